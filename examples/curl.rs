@@ -7,9 +7,9 @@ fn main() -> Result<(), curl::Error> {
     let url = "";
     let mut progress = Progress::new();
 
-    // In order to set the target total, you would need to know how big the data
-    // was ahead of time.
-    let bar: Bar = progress.bar(50, "Downloading...");
+    // We don't know how big the data is ahead of time, so we start with an
+    // indeterminate bar.
+    let bar: Bar = progress.indeterminate("Downloading...");
 
     // Establish our CURL settings.
     let mut handle = Easy::new();
@@ -18,7 +18,13 @@ fn main() -> Result<(), curl::Error> {
 
     // `progress_function` has aggressive lifetimes and requires the mutable
     // `progress` to be explicitly moved.
-    handle.progress_function(move |_, downloaded, _, _| {
+    handle.progress_function(move |total, downloaded, _, _| {
+        // CURL reports a total of 0 until the `Content-Length` is known, if
+        // ever. Once it is, our bar becomes a regular one.
+        if total > 0.0 {
+            progress.set_total(&bar, total as usize);
+        }
+
         progress.set_and_draw(&bar, downloaded as usize);
         true
     })?;
@@ -15,7 +15,7 @@ fn main() -> std::thread::Result<()> {
     let p1 = p0.clone();
 
     let child0 = std::thread::spawn(move || {
-        let bar = p0.lock().unwrap().bar(BAR_MAX, format!("Downloading #0"));
+        let bar = p0.lock().unwrap().bar(BAR_MAX, "Downloading #0");
 
         // Determine how fast our thread progresses.
         let wait = rand::thread_rng().gen_range(1..=10);
@@ -30,7 +30,7 @@ fn main() -> std::thread::Result<()> {
 
     // The same as the above.
     let child1 = std::thread::spawn(move || {
-        let bar = p1.lock().unwrap().bar(BAR_MAX, format!("Downloading #1"));
+        let bar = p1.lock().unwrap().bar(BAR_MAX, "Downloading #1");
         let wait = rand::thread_rng().gen_range(1..=10);
 
         for n in 0..=BAR_MAX {
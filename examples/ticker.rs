@@ -0,0 +1,41 @@
+//! An example of leaving all drawing to a background render thread.
+//!
+//! See the `multi` example for details on overall usage of the library.
+
+use linya::{Bar, Progress, SharedBar, Ticker};
+use rand::Rng;
+use rayon::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const BAR_MAX: usize = 1234;
+
+fn main() {
+    println!("Starting bars...");
+
+    // The render thread needs its own handle to `progress`, hence the `Arc`.
+    let progress = Arc::new(Mutex::new(Progress::new().with_rates(true)));
+    let ticker = Ticker::spawn(progress.clone(), 15);
+
+    (0..10).into_par_iter().for_each(|i| {
+        let shared: SharedBar = {
+            let mut p = progress.lock().unwrap();
+            let bar: Bar = p.bar(BAR_MAX, format!("Downloading #{}", i));
+            p.share(&bar)
+        };
+
+        let wait = rand::thread_rng().gen_range(1..=10);
+
+        for _ in 0..BAR_MAX {
+            // Only the counter is updated here, atomically and without taking
+            // the lock. The `Ticker` takes care of drawing.
+            shared.inc(1);
+            std::thread::sleep(Duration::from_millis(wait));
+        }
+    });
+
+    // Dropping the `Ticker` draws one last time and stops the thread.
+    drop(ticker);
+
+    println!("Complete!");
+}
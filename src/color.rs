@@ -0,0 +1,208 @@
+//! ANSI colours for bars.
+
+use std::fmt;
+
+/// A terminal colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// One of the 256 colours of the extended palette.
+    Fixed(u8),
+    /// A "true colour", for terminals that support them.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Use this colour for the foreground, on top of the given background.
+    ///
+    /// ```
+    /// use linya::Color;
+    ///
+    /// let paint = Color::Black.on(Color::Yellow);
+    /// ```
+    pub fn on(self, bg: Color) -> Paint {
+        Paint {
+            fg: Some(self),
+            bg: Some(bg),
+        }
+    }
+
+    /// Write the SGR parameters of this colour. The base is `30` for the
+    /// foreground and `40` for the background.
+    fn sgr(&self, f: &mut fmt::Formatter<'_>, base: u8) -> fmt::Result {
+        match *self {
+            Color::Black => write!(f, "{}", base),
+            Color::Red => write!(f, "{}", base + 1),
+            Color::Green => write!(f, "{}", base + 2),
+            Color::Yellow => write!(f, "{}", base + 3),
+            Color::Blue => write!(f, "{}", base + 4),
+            Color::Magenta => write!(f, "{}", base + 5),
+            Color::Cyan => write!(f, "{}", base + 6),
+            Color::White => write!(f, "{}", base + 7),
+            Color::BrightBlack => write!(f, "{}", base + 60),
+            Color::BrightRed => write!(f, "{}", base + 61),
+            Color::BrightGreen => write!(f, "{}", base + 62),
+            Color::BrightYellow => write!(f, "{}", base + 63),
+            Color::BrightBlue => write!(f, "{}", base + 64),
+            Color::BrightMagenta => write!(f, "{}", base + 65),
+            Color::BrightCyan => write!(f, "{}", base + 66),
+            Color::BrightWhite => write!(f, "{}", base + 67),
+            Color::Fixed(n) => write!(f, "{};5;{}", base + 8, n),
+            Color::Rgb(r, g, b) => write!(f, "{};2;{};{};{}", base + 8, r, g, b),
+        }
+    }
+}
+
+/// A foreground and background colour pair, either of which may be absent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Paint {
+    /// The foreground colour.
+    pub fg: Option<Color>,
+    /// The background colour.
+    pub bg: Option<Color>,
+}
+
+impl Paint {
+    /// Is there nothing to paint?
+    pub(crate) fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none()
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(fg: Color) -> Paint {
+        Paint {
+            fg: Some(fg),
+            bg: None,
+        }
+    }
+}
+
+/// The escape sequence that starts painting with the given colours.
+impl fmt::Display for Paint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1B[")?;
+
+        if let Some(fg) = self.fg {
+            fg.sgr(f, 30)?;
+        }
+
+        if let Some(bg) = self.bg {
+            if self.fg.is_some() {
+                write!(f, ";")?;
+            }
+            bg.sgr(f, 40)?;
+        }
+
+        write!(f, "m")
+    }
+}
+
+/// The escape sequence that resets all colours.
+pub(crate) const RESET: &str = "\x1B[0m";
+
+/// The colours of a bar, which differ by its state.
+///
+/// Set them for all bars with [`Progress::with_colors`], or for a single bar
+/// with [`Progress::set_colors`]:
+///
+/// ```
+/// use linya::{Color, Colors, Progress};
+///
+/// let colors = Colors::new()
+///     .with_progress(Color::Cyan)
+///     .with_done(Color::Green)
+///     .with_cancelled(Color::White.on(Color::Red));
+///
+/// let mut progress = Progress::new().with_colors(colors);
+/// ```
+///
+/// Colours are only shown when they are enabled for the `Progress`. See
+/// [`Progress::with_color`] and [`ColorChoice`].
+///
+/// [`Progress::with_colors`]: crate::Progress::with_colors
+/// [`Progress::set_colors`]: crate::Progress::set_colors
+/// [`Progress::with_color`]: crate::Progress::with_color
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Colors {
+    /// Bars that are still in progress.
+    pub(crate) progress: Paint,
+    /// Bars that have completed.
+    pub(crate) done: Paint,
+    /// Bars that were cancelled.
+    pub(crate) cancelled: Paint,
+}
+
+impl Colors {
+    /// No colours at all, the default.
+    pub fn new() -> Colors {
+        Colors::default()
+    }
+
+    /// Paint bars that are still in progress.
+    pub fn with_progress<P: Into<Paint>>(mut self, paint: P) -> Colors {
+        self.progress = paint.into();
+        self
+    }
+
+    /// Paint bars that have completed.
+    pub fn with_done<P: Into<Paint>>(mut self, paint: P) -> Colors {
+        self.done = paint.into();
+        self
+    }
+
+    /// Paint bars that were cancelled.
+    pub fn with_cancelled<P: Into<Paint>>(mut self, paint: P) -> Colors {
+        self.cancelled = paint.into();
+        self
+    }
+}
+
+/// Whether a [`Progress`](crate::Progress) should draw in colour.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ColorChoice {
+    /// Use colour if the output is a terminal, unless `NO_COLOR` is set or
+    /// `TERM` is `dumb`.
+    #[default]
+    Auto,
+    /// Always use colour.
+    Always,
+    /// Never use colour.
+    Never,
+}
+
+impl ColorChoice {
+    /// Should colour be used, given whether the output is a terminal?
+    pub(crate) fn resolve(self, tty: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let no_color = std::env::var_os("NO_COLOR")
+                    .map(|v| !v.is_empty())
+                    .unwrap_or(false);
+                let dumb = std::env::var_os("TERM")
+                    .map(|t| t == "dumb")
+                    .unwrap_or(false);
+
+                tty && !no_color && !dumb
+            }
+        }
+    }
+}
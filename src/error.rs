@@ -0,0 +1,28 @@
+//! Errors of the checked operations on bars.
+
+use std::fmt;
+
+/// Errors that can occur in the checked `try_*` operations of a
+/// [`Progress`](crate::Progress), which otherwise would panic or quietly do
+/// the wrong thing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// A bar with a total of `0`, which would be complete before it started.
+    ZeroTotal,
+    /// A [`Bar`](crate::Bar) made by another `Progress`.
+    ForeignBar,
+    /// A bar that has already completed or been cancelled.
+    Finished,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ZeroTotal => write!(f, "Bar has a total of 0"),
+            Error::ForeignBar => write!(f, "Bar belongs to another Progress"),
+            Error::Finished => write!(f, "Bar has already finished"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,125 @@
+//! Progress as a stream of JSON events, for other programs to consume.
+
+use std::fmt;
+use std::io::Write;
+
+/// The target of JSON events, with whatever a `Progress` needs to decide when
+/// to write them.
+pub(crate) struct Events {
+    out: Box<dyn Write + Send + Sync>,
+}
+
+impl Events {
+    pub(crate) fn new<E: Write + Send + Sync + 'static>(out: E) -> Events {
+        Events { out: Box::new(out) }
+    }
+
+    /// Write a single event as a line of JSON, and flush it straight away so
+    /// that the reader on the other end doesn't fall behind.
+    pub(crate) fn emit(&mut self, event: Event<'_>) {
+        let _ = writeln!(self.out, "{}", event);
+        let _ = self.out.flush();
+    }
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Events").finish_non_exhaustive()
+    }
+}
+
+/// Something that happened to a bar, or to the `Progress` as a whole.
+pub(crate) enum Event<'a> {
+    /// A bar was made.
+    Created {
+        bar: usize,
+        label: &'a str,
+        total: Option<usize>,
+        parent: Option<usize>,
+    },
+    /// A bar advanced.
+    Progress {
+        bar: usize,
+        value: usize,
+        total: Option<usize>,
+    },
+    /// A bar reached its total.
+    Finished { bar: usize, value: usize },
+    /// A bar was cancelled.
+    Cancelled { bar: usize, value: usize },
+    /// A message was written above the bars.
+    Message { text: &'a str },
+}
+
+/// A line of JSON, like `{"event":"progress","bar":0,"value":450,"total":1000}`.
+impl fmt::Display for Event<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Created {
+                bar,
+                label,
+                total,
+                parent,
+            } => {
+                write!(f, r#"{{"event":"bar_created","bar":{},"label":"#, bar)?;
+                string(f, label)?;
+                write!(f, r#","total":"#)?;
+                optional(f, *total)?;
+                write!(f, r#","parent":"#)?;
+                optional(f, *parent)?;
+                write!(f, "}}")
+            }
+            Event::Progress { bar, value, total } => {
+                write!(
+                    f,
+                    r#"{{"event":"progress","bar":{},"value":{},"total":"#,
+                    bar, value
+                )?;
+                optional(f, *total)?;
+                write!(f, "}}")
+            }
+            Event::Finished { bar, value } => write!(
+                f,
+                r#"{{"event":"finished","bar":{},"value":{}}}"#,
+                bar, value
+            ),
+            Event::Cancelled { bar, value } => write!(
+                f,
+                r#"{{"event":"cancelled","bar":{},"value":{}}}"#,
+                bar, value
+            ),
+            Event::Message { text } => {
+                write!(f, r#"{{"event":"message","text":"#)?;
+                string(f, text)?;
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// A number, or `null`.
+fn optional(f: &mut fmt::Formatter<'_>, n: Option<usize>) -> fmt::Result {
+    match n {
+        Some(n) => write!(f, "{}", n),
+        None => write!(f, "null"),
+    }
+}
+
+/// A quoted JSON string, with everything escaped that must be.
+fn string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+
+    write!(f, "\"")
+}
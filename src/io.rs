@@ -0,0 +1,532 @@
+//! Bars driven by reading and writing.
+
+use crate::{Bar, Coordinator, SharedBar, Tracker};
+use std::io::{self, BufRead, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::pin::Pin;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::task::{Context, Poll};
+
+/// A reader that advances a bar by the number of bytes read through it.
+///
+/// ```
+/// use linya::{Progress, ProgressReader};
+/// use std::io;
+///
+/// let data = vec![0u8; 4096];
+/// let mut progress = Progress::new();
+/// let bar = progress.bar(data.len(), "Copying");
+/// let mut reader = ProgressReader::new(&data[..], &mut progress, bar);
+///
+/// io::copy(&mut reader, &mut io::sink()).unwrap();
+/// ```
+///
+/// Its `BufRead` and `Seek` implementations pass through to the inner reader.
+/// Bytes taken via [`BufRead::consume`] count as read, while seeking moves the
+/// bar to the new position in the stream.
+///
+/// # Async
+///
+/// With the `tokio` or `futures` features, a `ProgressReader` also wraps the
+/// `AsyncRead`, `AsyncBufRead` and `AsyncSeek` traits of those crates:
+///
+/// ```
+/// # #[cfg(feature = "futures")] {
+/// use futures::{executor, io::AsyncReadExt};
+/// use linya::{Progress, ProgressReader};
+/// use std::sync::{Arc, Mutex};
+///
+/// let data = vec![0u8; 4096];
+/// let progress = Arc::new(Mutex::new(Progress::new()));
+/// let bar = progress.lock().unwrap().bar(data.len(), "Copying");
+/// let mut reader = ProgressReader::new(&data[..], progress.clone(), bar);
+///
+/// executor::block_on(async {
+///     let mut buf = Vec::new();
+///     reader.read_to_end(&mut buf).await.unwrap();
+/// });
+/// # }
+/// ```
+///
+/// See [the crate docs](crate#async) on sharing a `Progress` between tasks.
+#[derive(Debug)]
+pub struct ProgressReader<R, C> {
+    inner: R,
+    coordinator: C,
+    bar: Bar,
+}
+
+impl<R, C: Coordinator> ProgressReader<R, C> {
+    /// Advance the given [`Bar`] of the `Progress` reachable through
+    /// `coordinator` as `inner` is read from.
+    pub fn new(inner: R, coordinator: C, bar: Bar) -> ProgressReader<R, C> {
+        ProgressReader {
+            inner,
+            coordinator,
+            bar,
+        }
+    }
+}
+
+impl<R> ProgressReader<R, SharedBar> {
+    /// Advance the given [`SharedBar`] as `inner` is read from, without locking
+    /// its `Progress`. Drawing is left to a [`Ticker`](crate::Ticker).
+    pub fn shared(inner: R, bar: SharedBar) -> ProgressReader<R, SharedBar> {
+        ProgressReader {
+            inner,
+            bar: bar.as_bar().dup(),
+            coordinator: bar,
+        }
+    }
+}
+
+impl<R, C: Tracker> ProgressReader<R, C> {
+    /// The bar being advanced.
+    pub fn bar(&self) -> &Bar {
+        &self.bar
+    }
+
+    /// A reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// A mutable reference to the inner reader.
+    ///
+    /// Reading from it directly won't advance the bar.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap this `ProgressReader`, returning the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Unwrap this `ProgressReader`, returning the inner reader and the bar.
+    pub fn into_parts(self) -> (R, Bar) {
+        (self.inner, self.bar)
+    }
+
+    fn advance(&mut self, bytes: usize) {
+        self.coordinator.advance(&self.bar, bytes);
+    }
+}
+
+impl<R: Read, C: Tracker> Read for ProgressReader<R, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.advance(n);
+        Ok(n)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let n = self.inner.read_vectored(bufs)?;
+        self.advance(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead, C: Tracker> BufRead for ProgressReader<R, C> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.advance(amt);
+    }
+}
+
+impl<R: Seek, C: Tracker> Seek for ProgressReader<R, C> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = self.inner.seek(pos)?;
+        self.coordinator.move_to(&self.bar, pos as usize);
+        Ok(pos)
+    }
+}
+
+/// A writer that advances a bar by the number of bytes written through it.
+///
+/// ```
+/// use linya::{Progress, ProgressWriter};
+/// use std::io::Write;
+///
+/// let data = vec![0u8; 4096];
+/// let mut progress = Progress::new();
+/// let bar = progress.bar(data.len(), "Saving");
+/// let mut writer = ProgressWriter::new(Vec::new(), &mut progress, bar);
+///
+/// writer.write_all(&data).unwrap();
+/// ```
+///
+/// Its `Seek` implementation passes through to the inner writer, and moves the
+/// bar to the new position in the stream.
+///
+/// Like [`ProgressReader`], it also wraps the `AsyncWrite` and `AsyncSeek`
+/// traits of `tokio` or `futures`, when those features are enabled.
+#[derive(Debug)]
+pub struct ProgressWriter<W, C> {
+    inner: W,
+    coordinator: C,
+    bar: Bar,
+}
+
+impl<W, C: Coordinator> ProgressWriter<W, C> {
+    /// Advance the given [`Bar`] of the `Progress` reachable through
+    /// `coordinator` as `inner` is written to.
+    pub fn new(inner: W, coordinator: C, bar: Bar) -> ProgressWriter<W, C> {
+        ProgressWriter {
+            inner,
+            coordinator,
+            bar,
+        }
+    }
+}
+
+impl<W> ProgressWriter<W, SharedBar> {
+    /// Advance the given [`SharedBar`] as `inner` is written to, without locking
+    /// its `Progress`. Drawing is left to a [`Ticker`](crate::Ticker).
+    pub fn shared(inner: W, bar: SharedBar) -> ProgressWriter<W, SharedBar> {
+        ProgressWriter {
+            inner,
+            bar: bar.as_bar().dup(),
+            coordinator: bar,
+        }
+    }
+}
+
+impl<W, C: Tracker> ProgressWriter<W, C> {
+    /// The bar being advanced.
+    pub fn bar(&self) -> &Bar {
+        &self.bar
+    }
+
+    /// A reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// A mutable reference to the inner writer.
+    ///
+    /// Writing to it directly won't advance the bar.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap this `ProgressWriter`, returning the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Unwrap this `ProgressWriter`, returning the inner writer and the bar.
+    pub fn into_parts(self) -> (W, Bar) {
+        (self.inner, self.bar)
+    }
+
+    fn advance(&mut self, bytes: usize) {
+        self.coordinator.advance(&self.bar, bytes);
+    }
+}
+
+impl<W: Write, C: Tracker> Write for ProgressWriter<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.advance(n);
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let n = self.inner.write_vectored(bufs)?;
+        self.advance(n);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Seek, C: Tracker> Seek for ProgressWriter<W, C> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = self.inner.seek(pos)?;
+        self.coordinator.move_to(&self.bar, pos as usize);
+        Ok(pos)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R, C> tokio::io::AsyncRead for ProgressReader<R, C>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    C: Tracker + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(())) = poll {
+            this.advance(buf.filled().len() - before);
+        }
+
+        poll
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R, C> tokio::io::AsyncBufRead for ProgressReader<R, C>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+    C: Tracker + Unpin,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        Pin::new(&mut self.get_mut().inner).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        Pin::new(&mut this.inner).consume(amt);
+        this.advance(amt);
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R, C> tokio::io::AsyncSeek for ProgressReader<R, C>
+where
+    R: tokio::io::AsyncSeek + Unpin,
+    C: Tracker + Unpin,
+{
+    fn start_seek(self: Pin<&mut Self>, pos: SeekFrom) -> io::Result<()> {
+        Pin::new(&mut self.get_mut().inner).start_seek(pos)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_complete(cx);
+
+        if let Poll::Ready(Ok(pos)) = poll {
+            this.coordinator.move_to(&this.bar, pos as usize);
+        }
+
+        poll
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W, C> tokio::io::AsyncWrite for ProgressWriter<W, C>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    C: Tracker + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+
+        if let Poll::Ready(Ok(n)) = poll {
+            this.advance(n);
+        }
+
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+
+        if let Poll::Ready(Ok(n)) = poll {
+            this.advance(n);
+        }
+
+        poll
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W, C> tokio::io::AsyncSeek for ProgressWriter<W, C>
+where
+    W: tokio::io::AsyncSeek + Unpin,
+    C: Tracker + Unpin,
+{
+    fn start_seek(self: Pin<&mut Self>, pos: SeekFrom) -> io::Result<()> {
+        Pin::new(&mut self.get_mut().inner).start_seek(pos)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_complete(cx);
+
+        if let Poll::Ready(Ok(pos)) = poll {
+            this.coordinator.move_to(&this.bar, pos as usize);
+        }
+
+        poll
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R, C> futures_io::AsyncRead for ProgressReader<R, C>
+where
+    R: futures_io::AsyncRead + Unpin,
+    C: Tracker + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(n)) = poll {
+            this.advance(n);
+        }
+
+        poll
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_read_vectored(cx, bufs);
+
+        if let Poll::Ready(Ok(n)) = poll {
+            this.advance(n);
+        }
+
+        poll
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R, C> futures_io::AsyncBufRead for ProgressReader<R, C>
+where
+    R: futures_io::AsyncBufRead + Unpin,
+    C: Tracker + Unpin,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        Pin::new(&mut self.get_mut().inner).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        Pin::new(&mut this.inner).consume(amt);
+        this.advance(amt);
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R, C> futures_io::AsyncSeek for ProgressReader<R, C>
+where
+    R: futures_io::AsyncSeek + Unpin,
+    C: Tracker + Unpin,
+{
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_seek(cx, pos);
+
+        if let Poll::Ready(Ok(pos)) = poll {
+            this.coordinator.move_to(&this.bar, pos as usize);
+        }
+
+        poll
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<W, C> futures_io::AsyncWrite for ProgressWriter<W, C>
+where
+    W: futures_io::AsyncWrite + Unpin,
+    C: Tracker + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+
+        if let Poll::Ready(Ok(n)) = poll {
+            this.advance(n);
+        }
+
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+
+        if let Poll::Ready(Ok(n)) = poll {
+            this.advance(n);
+        }
+
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<W, C> futures_io::AsyncSeek for ProgressWriter<W, C>
+where
+    W: futures_io::AsyncSeek + Unpin,
+    C: Tracker + Unpin,
+{
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_seek(cx, pos);
+
+        if let Poll::Ready(Ok(pos)) = poll {
+            this.coordinator.move_to(&this.bar, pos as usize);
+        }
+
+        poll
+    }
+}
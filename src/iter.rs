@@ -0,0 +1,290 @@
+//! Bars driven by iterators.
+
+use crate::{Bar, OnFinish, Progress, SharedBar};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Anything through which a [`Progress`] can be reached: a plain mutable
+/// reference, or a `Mutex` shared between threads.
+///
+/// A poisoned `Mutex` is used regardless, as a panic elsewhere is no reason to
+/// stop drawing.
+pub trait Coordinator {
+    /// The output target of the `Progress`.
+    type Writer: Write;
+
+    /// Run the given function with exclusive access to the `Progress`.
+    fn with<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Progress<Self::Writer>) -> R;
+}
+
+impl<W: Write> Coordinator for &mut Progress<W> {
+    type Writer = W;
+
+    fn with<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Progress<W>) -> R,
+    {
+        f(self)
+    }
+}
+
+impl<W: Write> Coordinator for &Mutex<Progress<W>> {
+    type Writer = W;
+
+    fn with<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Progress<W>) -> R,
+    {
+        f(&mut self.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl<W: Write> Coordinator for Arc<Mutex<Progress<W>>> {
+    type Writer = W;
+
+    fn with<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Progress<W>) -> R,
+    {
+        f(&mut self.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Anything through which a bar can be advanced: any [`Coordinator`], which
+/// draws the bar as it goes, or a [`SharedBar`], whose progress is only
+/// counted and left for someone else to draw, usually a
+/// [`Ticker`](crate::Ticker).
+pub trait Tracker {
+    /// Move the bar forward by `value`.
+    fn advance(&mut self, bar: &Bar, value: usize);
+
+    /// Move the bar to `value`.
+    fn move_to(&mut self, bar: &Bar, value: usize);
+
+    /// The bar has run its course, after `count` steps.
+    fn finish(&mut self, bar: &Bar, count: usize);
+
+    /// The bar has been given up on before running its course.
+    fn abandon(&mut self, bar: Bar);
+}
+
+impl<C: Coordinator> Tracker for C {
+    fn advance(&mut self, bar: &Bar, value: usize) {
+        self.with(|p| p.inc_and_draw(bar, value));
+    }
+
+    fn move_to(&mut self, bar: &Bar, value: usize) {
+        self.with(|p| p.set_and_draw(bar, value));
+    }
+
+    fn finish(&mut self, bar: &Bar, count: usize) {
+        // The total of an indeterminate bar, or a wrong one, is corrected now
+        // that the true count is known.
+        self.with(|p| {
+            p.set_total(bar, count);
+            p.draw(bar);
+        });
+    }
+
+    fn abandon(&mut self, bar: Bar) {
+        // The last item may well have been taken without asking for more.
+        self.with(|p| {
+            if !p.is_done(&bar) {
+                p.cancel(bar);
+            }
+        });
+    }
+}
+
+/// Without a `Progress` at hand, a [`SharedBar`] can neither correct its total
+/// nor be cancelled, and only ever has its progress updated.
+impl Tracker for SharedBar {
+    fn advance(&mut self, _: &Bar, value: usize) {
+        self.inc(value);
+    }
+
+    fn move_to(&mut self, _: &Bar, value: usize) {
+        self.set(value);
+    }
+
+    fn finish(&mut self, _: &Bar, _: usize) {}
+
+    fn abandon(&mut self, _: Bar) {}
+}
+
+/// Drive a bar automatically by iterating.
+///
+/// ```
+/// use linya::{Progress, ProgressIterator};
+///
+/// let mut progress = Progress::new();
+///
+/// for n in (0..100).progress_with(&mut progress, "Counting") {
+///     // ... Your logic ...
+/// }
+/// ```
+///
+/// This works just as well with a `Progress` shared between threads:
+///
+/// ```
+/// use linya::{Progress, ProgressIterator};
+/// use std::sync::Mutex;
+///
+/// let progress = Mutex::new(Progress::new());
+///
+/// std::thread::scope(|s| {
+///     for t in 0..4 {
+///         let progress = &progress;
+///         s.spawn(move || {
+///             let label = format!("Thread {}", t);
+///             (0..100).progress_with(progress, label).for_each(|_| {});
+///         });
+///     }
+/// });
+/// ```
+pub trait ProgressIterator: Iterator + Sized {
+    /// Create a bar for this iterator and advance it with every item.
+    ///
+    /// The bar's total comes from the iterator's
+    /// [`size_hint`](Iterator::size_hint) if that is exact, and otherwise the
+    /// bar is [indeterminate](Progress::indeterminate) until the iterator is
+    /// exhausted. If the iterator is dropped before that, the bar is cancelled.
+    fn progress_with<C, S>(self, coordinator: C, label: S) -> Progressed<Self, C>
+    where
+        C: Coordinator,
+        S: Into<String>,
+    {
+        Progressed::new(self, coordinator, label.into())
+    }
+}
+
+impl<I: Iterator> ProgressIterator for I {}
+
+/// An iterator that advances a bar with every item. See [`ProgressIterator`].
+///
+/// With the `futures` feature, this is also the stream returned by
+/// `ProgressStream::progress_with`.
+#[derive(Debug)]
+pub struct Progressed<I, C: Tracker> {
+    iter: I,
+    coordinator: C,
+    bar: Bar,
+    /// The number of items so far.
+    count: usize,
+    /// Has the iterator run out of items?
+    exhausted: bool,
+}
+
+impl<I: Iterator, C: Coordinator> Progressed<I, C> {
+    fn new(iter: I, coordinator: C, label: String) -> Progressed<I, C> {
+        let hint = iter.size_hint();
+        Progressed::start(iter, coordinator, label, hint)
+    }
+}
+
+impl<I, C: Coordinator> Progressed<I, C> {
+    /// Create the bar, sized by the given hint if it is exact.
+    pub(crate) fn start(
+        iter: I,
+        mut coordinator: C,
+        label: String,
+        hint: (usize, Option<usize>),
+    ) -> Progressed<I, C> {
+        let bar = coordinator.with(|p| match hint {
+            (lo, Some(hi)) if lo == hi => p.bar(hi, label),
+            _ => p.indeterminate(label),
+        });
+
+        Progressed {
+            iter,
+            coordinator,
+            bar,
+            count: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Decide what becomes of the bar once it has finished. See [`OnFinish`].
+    ///
+    /// ```
+    /// use linya::{OnFinish, Progress, ProgressIterator};
+    ///
+    /// let mut progress = Progress::new();
+    /// let sum: u32 = (0..100)
+    ///     .progress_with(&mut progress, "Summing")
+    ///     .with_on_finish(OnFinish::Clear)
+    ///     .sum();
+    /// ```
+    pub fn with_on_finish(mut self, on_finish: OnFinish) -> Progressed<I, C> {
+        let bar = &self.bar;
+        self.coordinator.with(|p| p.set_on_finish(bar, on_finish));
+        self
+    }
+}
+
+impl<I> Progressed<I, SharedBar> {
+    /// Advance an existing bar, without ever locking its `Progress`.
+    #[cfg(feature = "futures")]
+    pub(crate) fn shared(iter: I, shared: SharedBar) -> Progressed<I, SharedBar> {
+        Progressed {
+            iter,
+            bar: shared.as_bar().dup(),
+            coordinator: shared,
+            count: 0,
+            exhausted: false,
+        }
+    }
+}
+
+impl<I, C: Tracker> Progressed<I, C> {
+    /// The wrapped iterator or stream.
+    #[cfg(feature = "futures")]
+    pub(crate) fn inner(&self) -> &I {
+        &self.iter
+    }
+
+    /// The wrapped iterator or stream, mutably.
+    #[cfg(feature = "futures")]
+    pub(crate) fn inner_mut(&mut self) -> &mut I {
+        &mut self.iter
+    }
+
+    /// Account for the next item, or for the lack of one.
+    pub(crate) fn step(&mut self, item: bool) {
+        let bar = &self.bar;
+
+        if item {
+            self.count += 1;
+            self.coordinator.advance(bar, 1);
+        } else if !self.exhausted {
+            self.exhausted = true;
+            self.coordinator.finish(bar, self.count);
+        }
+    }
+}
+
+impl<I: Iterator, C: Tracker> Iterator for Progressed<I, C> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let item = self.iter.next();
+        self.step(item.is_some());
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator, C: Tracker> ExactSizeIterator for Progressed<I, C> {}
+
+impl<I, C: Tracker> Drop for Progressed<I, C> {
+    fn drop(&mut self) {
+        if !self.exhausted {
+            self.coordinator.abandon(self.bar.dup());
+        }
+    }
+}
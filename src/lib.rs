@@ -48,6 +48,21 @@
 //! See [`Progress::inc_and_draw`] and [`Progress::set_and_draw`] to advance and
 //! render the bars.
 //!
+//! With the `rayon` feature enabled, `ParallelProgressIterator` instead drives
+//! a single bar from a parallel iterator, with no locking of your own:
+//!
+//! ```
+//! # #[cfg(feature = "rayon")] {
+//! use linya::{ParallelProgressIterator, Progress};
+//! use rayon::prelude::*;
+//!
+//! let mut progress = Progress::new();
+//! (0..1000).into_par_iter().progress_with(&mut progress, "Crunching").for_each(|n| {
+//!   // ... Your logic ...
+//! });
+//! # }
+//! ```
+//!
 //! ## Single Bars
 //!
 //! `Progress` can also be used in a single-threaded context for individual
@@ -65,24 +80,52 @@
 //!
 //! In this way, you could even have multi-bars in a single-threaded context.
 //!
+//! ## Background Rendering
+//!
+//! Drawing can optionally be left to a [`Ticker`], a dedicated render thread
+//! that redraws all bars at a fixed frame rate. Worker threads then only need
+//! to update their bars with [`Progress::inc`] or [`Progress::set`].
+//!
+//! Better yet, [`Progress::share`] provides a [`SharedBar`], a handle that can
+//! be cloned and sent between threads, and whose progress is updated
+//! atomically without locking the `Progress` at all.
+//!
+//! ## Async
+//!
+//! With the `futures` feature, `ProgressStream` drives a bar from a `Stream`,
+//! and `ProgressReader` and `ProgressWriter` also wrap the `AsyncRead` and
+//! `AsyncWrite` traits of `futures`. The `tokio` feature does the same for
+//! `tokio`'s traits. These only reach the `Progress` from within a poll, never
+//! across an `.await`, so an `Arc<Mutex<Progress>>` is safe to share between
+//! tasks:
+//!
+//! ```
+//! # #[cfg(feature = "futures")] {
+//! use futures::stream;
+//! use linya::{Progress, ProgressStream};
+//! use std::sync::{Arc, Mutex};
+//!
+//! let progress = Arc::new(Mutex::new(Progress::new()));
+//! let stream = stream::iter(0..100).progress_with(progress.clone(), "Streaming");
+//! # }
+//! ```
+//!
+//! Each poll still locks the `Progress` and draws on the executor's thread,
+//! though. To avoid that, drive a [`SharedBar`] instead, via
+//! `ProgressStream::progress_shared`, `ProgressReader::shared` or
+//! `ProgressWriter::shared`, and leave the drawing to a [`Ticker`].
+//!
 //! # Caveats
 //!
 //! Some of the points below may be fixed in future releases.
 //!
 //! - Your terminal must support ANSI codes.
-//! - No dedicated render thread, to keep usage simple.
-//! - No bar templating, to avoid dependencies.
-//! - No other bar styling ([yet]).
-//! - No "rates", since rerenders are not time-based.
-//! - No bar clearing after completion.
-//! - No spinners, also due to no sense of time.
-//! - No dynamic resizing of bars if window size changes.
 //!
 //! If you need more customizable progress bars and are willing to accept
 //! heavier dependencies, please consider [indicatif].
 //!
-//! Note also that using more than one `Progress` at the same time leads to
-//! unspecified behaviour.
+//! Note also that drawing more than one `Progress` to the same terminal at the
+//! same time leads to unspecified behaviour.
 //!
 //! # Trivia
 //!
@@ -90,27 +133,126 @@
 //!
 //! [mirrormere]: https://www.tednasmith.com/tolkien/durins-crown-and-the-mirrormere/
 //! [arcmutex]: https://doc.rust-lang.org/stable/book/ch16-03-shared-state.html?#atomic-reference-counting-with-arct
-//! [yet]: https://internals.rust-lang.org/t/fmt-dynamic-fill-character/13609
 //! [indicatif]: https://lib.rs/crates/indicatif
 
 #![warn(missing_docs)]
 #![doc(html_root_url = "https://docs.rs/linya/0.3.0")]
 
+use event::{Event, Events};
 use std::fmt;
-use std::io::{BufWriter, Stderr, Write};
-use terminal_size::{terminal_size, Height, Width};
+use std::io::{BufWriter, IsTerminal, Stderr, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use terminal_size::{Height, Width};
+
+mod color;
+mod error;
+mod event;
+mod io;
+mod iter;
+#[cfg(feature = "rayon")]
+mod par;
+mod plain;
+mod rate;
+mod remote;
+mod render;
+mod shared;
+#[cfg(feature = "futures")]
+mod stream;
+mod style;
+mod template;
+mod ticker;
+mod vt;
+
+pub use color::{Color, ColorChoice, Colors, Paint};
+pub use error::Error;
+pub use io::{ProgressReader, ProgressWriter};
+pub use iter::{Coordinator, ProgressIterator, Progressed, Tracker};
+#[cfg(feature = "rayon")]
+pub use par::{ParProgressed, ParallelProgressIterator};
+pub use plain::Plain;
+use rate::Rate;
+pub use remote::{ProgressClient, ProgressServer, RemoteBar};
+use render::{Look, RATES_REFRESH, SPIN_INTERVAL};
+pub use shared::SharedBar;
+#[cfg(feature = "futures")]
+pub use stream::ProgressStream;
+pub use style::BarStyle;
+pub use template::{Template, TemplateError};
+pub use ticker::Ticker;
+pub use vt::VirtualTerminal;
+
+/// The width of the elapsed time, throughput and ETA shown after each bar when
+/// rates are enabled, e.g. ` 00:12 123K/s ETA 00:34`.
+const RATES_WIDTH: usize = 23;
+
+/// How often the terminal is measured again, to notice when it is resized.
+const RESIZE_CHECK: Duration = Duration::from_millis(250);
+
+/// Bars that advance without moving a full percent report their progress as
+/// an event at most this often.
+const EVENT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The identifier of the next `Progress` to be made, so that bars can be told
+/// apart from those of other instances.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 
 /// A progress bar "coordinator" to share between threads.
+///
+/// By default bars are rendered to a buffered handle to `Stderr`, but any
+/// other [`Write`] target can be chosen via [`Progress::with_writer`].
 #[derive(Debug)]
-pub struct Progress {
+pub struct Progress<W: Write = BufWriter<Stderr>> {
+    /// Unique to this instance, and shared by all of its [`Bar`] handles.
+    id: usize,
     /// The drawable bars themselves.
     bars: Vec<SubBar>,
-    /// A shared handle to `Stderr`.
+    /// The number of lines drawn below the bars' own output, the summary of
+    /// folded bars included.
+    lines: usize,
+    /// The bars that had to be folded away to fit the terminal, if any.
+    folded: Option<Folded>,
+    /// The bars whose progress is derived from that of others.
+    aggregates: Vec<usize>,
+    /// The output target, by default a shared handle to `Stderr`.
     ///
     /// Buffered so that the cursor doesn't jump around unpleasantly.
-    out: BufWriter<Stderr>,
+    out: W,
     /// Terminal width and height.
     size: Option<(usize, usize)>,
+    /// When the terminal was last measured, or `None` if its size is fixed.
+    measured: Option<Instant>,
+    /// Show elapsed time, throughput and an ETA for each bar?
+    rates: bool,
+    /// The layout of bars that don't have their own template.
+    template: Option<Template>,
+    /// The look of bars that don't have their own style.
+    style: BarStyle,
+    /// The colours of bars that don't have their own.
+    colors: Colors,
+    /// What becomes of bars that don't have their own policy, once finished.
+    on_finish: OnFinish,
+    /// How often bars are reported as plain text, when not drawn in place.
+    plain: Plain,
+    /// Where to send JSON events about the bars, if anywhere.
+    events: Option<Events>,
+    /// How to show progress in the terminal's tab or title, if at all.
+    mirror: Option<Mirror>,
+    /// The bar shown in the terminal's tab or title, or `None` for the
+    /// overall progress.
+    mirrored: Option<usize>,
+    /// The percent last shown in the terminal's tab or title, `Some(None)` for
+    /// progress of unknown total, or `None` if nothing is shown.
+    shown: Option<Option<usize>>,
+    /// Must the tab or title be drawn again, even if the percent is the same?
+    mirror_dirty: bool,
+    /// Is the output a terminal?
+    tty: bool,
+    /// Should bars be drawn in colour?
+    colored: bool,
+    /// Scratch space for rendering bar lines, reused between draws.
+    line: String,
 }
 
 impl Default for Progress {
@@ -125,75 +267,1013 @@ impl Default for Progress {
 // piping scenarios.
 impl Progress {
     /// Initialize a new progress bar coordinator.
+    ///
+    /// If `Stderr` isn't a terminal, say when redirected to a log file, bars
+    /// are reported as [plain](Progress::with_plain) lines of text instead.
     pub fn new() -> Progress {
-        let out = BufWriter::new(std::io::stderr());
-        let bars = vec![];
-        let size = terminal_size().map(|(Width(w), Height(h))| (w as usize, h as usize));
-        Progress { bars, out, size }
+        let mut progress = Progress::with_writer(BufWriter::new(std::io::stderr()));
+        progress.tty = std::io::stderr().is_terminal();
+        progress.colored = ColorChoice::Auto.resolve(progress.tty);
+
+        if !progress.tty {
+            progress.size = None;
+            progress.measured = None;
+        }
+
+        progress
     }
 
     /// Like [`Progress::new`] but accepts a size hint to avoid reallocation as bar count grows.
     pub fn with_capacity(capacity: usize) -> Progress {
-        let out = BufWriter::new(std::io::stderr());
-        let bars = Vec::with_capacity(capacity);
-        let size = terminal_size().map(|(Width(w), Height(h))| (w as usize, h as usize));
-        Progress { bars, out, size }
+        let mut progress = Progress::new();
+        progress.bars.reserve_exact(capacity);
+        progress
+    }
+}
+
+impl<W: Write> Progress<W> {
+    /// Initialize a new progress bar coordinator that renders to the given
+    /// output target instead of `Stderr`.
+    ///
+    /// The terminal size is still measured as in [`Progress::new`]. If `out`
+    /// isn't attached to the current terminal (say it's a file or an in-memory
+    /// buffer), you likely want to fix the size with [`Progress::with_size`]
+    /// as well.
+    ///
+    /// Note that `out` is written to frequently, so it should usually be
+    /// buffered.
+    ///
+    /// ```
+    /// use linya::Progress;
+    ///
+    /// let mut progress = Progress::with_writer(Vec::new()).with_size(80, 24);
+    /// let bar = progress.bar(50, "Downloading");
+    /// progress.set_and_draw(&bar, 10);
+    ///
+    /// assert!(!progress.writer().is_empty());
+    /// ```
+    pub fn with_writer(out: W) -> Progress<W> {
+        let bars = vec![];
+        let size = measure();
+        Progress {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            bars,
+            lines: 0,
+            folded: None,
+            aggregates: vec![],
+            out,
+            size,
+            measured: size.map(|_| Instant::now()),
+            rates: false,
+            template: None,
+            style: BarStyle::default(),
+            colors: Colors::default(),
+            on_finish: OnFinish::default(),
+            plain: Plain::default(),
+            events: None,
+            mirror: None,
+            mirrored: None,
+            shown: None,
+            mirror_dirty: false,
+            tty: false,
+            colored: false,
+            line: String::new(),
+        }
+    }
+
+    /// Fix the width and height of the drawing area, instead of using the
+    /// measured terminal size.
+    ///
+    /// The terminal is then no longer measured again when drawing, so any
+    /// later changes in size must be given via [`Progress::resize`].
+    pub fn with_size(mut self, width: usize, height: usize) -> Progress<W> {
+        self.size = Some((width, height));
+        self.measured = None;
+        self
+    }
+
+    /// Show the elapsed time, the throughput and an estimated time of arrival
+    /// to the right of each bar.
+    ///
+    /// The throughput is measured over the last several seconds, so that bursty
+    /// progress doesn't make the ETA jump around. Like the progress value
+    /// itself, it is scaled to `K`, `M`, or `G` per second when large.
+    ///
+    /// ```
+    /// use linya::Progress;
+    ///
+    /// let mut progress = Progress::new().with_rates(true);
+    /// let bar = progress.bar(1000, "Downloading");
+    /// progress.inc_and_draw(&bar, 100);
+    /// ```
+    pub fn with_rates(mut self, rates: bool) -> Progress<W> {
+        self.rates = rates;
+        self
+    }
+
+    /// Lay out all bars according to the given [`Template`], unless they have
+    /// one of their own.
+    ///
+    /// ```
+    /// use linya::{Progress, Template};
+    ///
+    /// let template = Template::new("{label}: {percent} [{bar}] {count} @ {rate}").unwrap();
+    /// let mut progress = Progress::new().with_template(template);
+    /// ```
+    pub fn with_template(mut self, template: Template) -> Progress<W> {
+        self.template = Some(template);
+        self
+    }
+
+    /// Draw all bars with the given [`BarStyle`], unless they have one of
+    /// their own.
+    ///
+    /// ```
+    /// use linya::{BarStyle, Progress};
+    ///
+    /// let mut progress = Progress::new().with_style(BarStyle::blocks());
+    /// ```
+    pub fn with_style(mut self, style: BarStyle) -> Progress<W> {
+        self.style = style;
+        self
+    }
+
+    /// Paint all bars with the given [`Colors`], unless they have their own.
+    ///
+    /// Whether colours are actually shown is decided by
+    /// [`Progress::with_color`].
+    pub fn with_colors(mut self, colors: Colors) -> Progress<W> {
+        self.colors = colors;
+        self
+    }
+
+    /// Decide whether bars are drawn in colour.
+    ///
+    /// The default is [`ColorChoice::Auto`]: colours are shown if the output is
+    /// a terminal, unless the `NO_COLOR` environment variable is set or `TERM`
+    /// is `dumb`. The target of [`Progress::with_writer`] is never assumed to
+    /// be a terminal, so it needs [`ColorChoice::Always`] to show colours.
+    ///
+    /// ```
+    /// use linya::{Color, ColorChoice, Colors, Progress};
+    ///
+    /// let mut progress = Progress::new()
+    ///     .with_colors(Colors::new().with_done(Color::Green))
+    ///     .with_color(ColorChoice::Always);
+    /// ```
+    pub fn with_color(mut self, choice: ColorChoice) -> Progress<W> {
+        self.colored = choice.resolve(self.tty);
+        self
+    }
+
+    /// Decide what becomes of bars once they've finished, unless they have a
+    /// policy of their own. See [`OnFinish`].
+    ///
+    /// ```
+    /// use linya::{OnFinish, Progress};
+    ///
+    /// let mut progress = Progress::new().with_on_finish(OnFinish::Clear);
+    /// ```
+    pub fn with_on_finish(mut self, on_finish: OnFinish) -> Progress<W> {
+        self.on_finish = on_finish;
+        self
+    }
+
+    /// Report progress as plain, append-only lines of text, throttled as given
+    /// by [`Plain`], instead of drawing bars in place.
+    ///
+    /// This is done anyway, with the default throttling, when there is no
+    /// terminal to draw on. Suited to logs and CI, it looks like:
+    ///
+    /// ```text
+    /// Downloading: 0% (0/1000)
+    /// Downloading: 10% (100/1000)
+    /// Downloading: 20% (207/1000)
+    /// ```
+    ///
+    /// A later call to [`Progress::resize`] draws in place again.
+    pub fn with_plain(mut self, plain: Plain) -> Progress<W> {
+        self.plain = plain;
+        self.size = None;
+        self.measured = None;
+        self
+    }
+
+    /// Also describe what happens to the bars as JSON events, one per line,
+    /// written to the given target.
+    ///
+    /// This lets another program, say a GUI or a job runner, follow along
+    /// without having to make sense of ANSI escape codes:
+    ///
+    /// ```text
+    /// {"event":"bar_created","bar":0,"label":"Downloading","total":1000,"parent":null}
+    /// {"event":"progress","bar":0,"value":450,"total":1000}
+    /// {"event":"message","text":"Mirror is slow"}
+    /// {"event":"progress","bar":0,"value":1000,"total":1000}
+    /// {"event":"finished","bar":0,"value":1000}
+    /// ```
+    ///
+    /// A bar whose progress is `set` but never drawn won't report it, and
+    /// progress is reported at most every percent, or every 100ms for bars of
+    /// unknown total. A `cancelled` event takes the place of `finished` for a
+    /// cancelled bar, with the progress it had made when it was cancelled.
+    /// Messages are those written via [`Progress::stderr`].
+    ///
+    /// The bars are still drawn as usual. To only have the events, render to
+    /// [`std::io::sink`]:
+    ///
+    /// ```
+    /// use linya::Progress;
+    ///
+    /// let mut progress = Progress::with_writer(std::io::sink()).with_events(std::io::stdout());
+    /// let bar = progress.bar(1000, "Downloading");
+    /// progress.inc_and_draw(&bar, 450);
+    /// ```
+    pub fn with_events<E: Write + Send + Sync + 'static>(mut self, events: E) -> Progress<W> {
+        self.events = Some(Events::new(events));
+        self
+    }
+
+    /// Also show progress in the terminal's tab, taskbar or window title. See
+    /// [`Mirror`].
+    ///
+    /// By default the overall progress of all bars is shown, as would be by an
+    /// [aggregate](Progress::aggregate) bar. Choose a single bar instead with
+    /// [`Progress::mirror_bar`].
+    ///
+    /// ```
+    /// use linya::{Mirror, Progress};
+    ///
+    /// let mut progress = Progress::new().with_mirror(Mirror::Taskbar);
+    /// ```
+    ///
+    /// The terminal is cleared of it once all is done or cancelled, and when
+    /// the `Progress` is dropped.
+    pub fn with_mirror(mut self, mirror: Mirror) -> Progress<W> {
+        self.mirror = Some(mirror);
+        self
+    }
+
+    /// A reference to the underlying output target.
+    pub fn writer(&self) -> &W {
+        &self.out
+    }
+
+    /// A mutable reference to the underlying output target.
+    ///
+    /// Writing to it directly will likely interfere with the rendering of the
+    /// bars. See [`Progress::stderr`] for a safe way to print messages.
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.out
     }
 
     /// Create a new progress bar with default styling and receive an owned
     /// handle to it.
     ///
-    /// # Panics
-    ///
-    /// Passing `0` to this function will cause a panic the first time a draw is
-    /// attempted.
+    /// A bar with a total of `0` is complete from the start.
     pub fn bar<S: Into<String>>(&mut self, total: usize, label: S) -> Bar {
-        let twidth = self.size.map(|(w, _)| w).unwrap_or(100);
-        let w = (twidth / 2) - 7;
-        let label: String = label.into();
-
-        // An initial "empty" rendering of the new bar.
-        let _ = writeln!(
-            self.out,
-            "{:<l$}      [{:->f$}]   0%",
-            label,
-            "",
-            l = twidth - w - 8 - 5,
-            f = w
-        );
-        let _ = self.out.flush();
+        self.push_bar(SubBar::new(Some(total), label.into()))
+    }
+
+    /// Like [`Progress::bar`], but refuse a total of `0`.
+    ///
+    /// ```
+    /// use linya::{Error, Progress};
+    ///
+    /// let mut progress = Progress::new();
+    /// assert_eq!(Some(Error::ZeroTotal), progress.try_bar(0, "Empty").err());
+    /// ```
+    pub fn try_bar<S: Into<String>>(&mut self, total: usize, label: S) -> Result<Bar, Error> {
+        match total {
+            0 => Err(Error::ZeroTotal),
+            _ => Ok(self.bar(total, label)),
+        }
+    }
+
+    /// Create a new indeterminate progress bar, for work whose total isn't
+    /// known ahead of time.
+    ///
+    /// Instead of filling up, an indeterminate bar shows a bouncing block and a
+    /// spinner next to its running count. Once the total becomes known, it can
+    /// be turned into a regular bar with [`Progress::set_total`].
+    ///
+    /// ```
+    /// use linya::Progress;
+    ///
+    /// let mut progress = Progress::new();
+    /// let bar = progress.indeterminate("Downloading");
+    /// progress.inc_and_draw(&bar, 1024);
+    ///
+    /// // Say a `Content-Length` header finally arrived.
+    /// progress.set_total(&bar, 4096);
+    /// progress.inc_and_draw(&bar, 1024);
+    /// ```
+    pub fn indeterminate<S: Into<String>>(&mut self, label: S) -> Bar {
+        self.push_bar(SubBar::new(None, label.into()))
+    }
+
+    /// Create a bar that tracks the overall progress of all other bars,
+    /// pinned above or below them.
+    ///
+    /// Its progress and total are the sums of those of every other bar, bars
+    /// created later included. [Parents](Progress::child_bar) are left out,
+    /// so that nothing is counted twice. Cancelled bars count as complete, while an
+    /// [indeterminate](Progress::indeterminate) bar leaves the total unknown
+    /// until its own is set. The aggregate bar is redrawn along with any bar it
+    /// tracks, and its progress can't be set by hand.
+    ///
+    /// A pinned bar always has a line of its own, even when other bars had to
+    /// be folded away to fit the terminal.
+    ///
+    /// ```
+    /// use linya::{Pin, Progress};
+    ///
+    /// let mut progress = Progress::new();
+    /// let total = progress.aggregate("Overall", Pin::Top);
+    /// let a = progress.bar(50, "Downloading A");
+    /// let b = progress.bar(50, "Downloading B");
+    ///
+    /// progress.inc_and_draw(&a, 50);
+    /// progress.inc_and_draw(&b, 25);
+    /// assert!(!progress.is_done(&total));
+    /// ```
+    pub fn aggregate<S: Into<String>>(&mut self, label: S, pin: Pin) -> Bar {
+        let mut bar = SubBar::new(None, label.into());
+        bar.pin = Some(pin);
+        bar.source = Some(Source::All);
+        self.push_bar(bar)
+    }
+
+    /// Like [`Progress::aggregate`], but only tracks the given bars.
+    ///
+    /// ```
+    /// use linya::{Pin, Progress};
+    ///
+    /// let mut progress = Progress::new();
+    /// let a = progress.bar(50, "Downloading A");
+    /// let b = progress.bar(50, "Downloading B");
+    /// let c = progress.bar(50, "Unpacking");
+    /// let downloads = progress.aggregate_of("Downloads", Pin::Bottom, &[&a, &b]);
+    /// ```
+    pub fn aggregate_of<S: Into<String>>(&mut self, label: S, pin: Pin, bars: &[&Bar]) -> Bar {
+        let mut bar = SubBar::new(None, label.into());
+        bar.pin = Some(pin);
+        bar.source = Some(Source::Group(bars.iter().map(|b| self.index(b)).collect()));
+        self.push_bar(bar)
+    }
+
+    /// Create a bar nested under another, and receive an owned handle to it.
+    ///
+    /// Child bars are drawn indented below their parent, after any earlier
+    /// children. The parent's progress is set as usual, unless it is derived
+    /// from its children via [`Progress::sum_children`]. Once the parent has
+    /// finished, its children are collapsed into it and no longer shown.
+    ///
+    /// ```
+    /// use linya::Progress;
+    ///
+    /// let mut progress = Progress::new();
+    /// let build = progress.bar(2, "Build");
+    /// let core = progress.child_bar(&build, 120, "core");
+    /// let cli = progress.child_bar(&build, 40, "cli");
+    ///
+    /// progress.inc_and_draw(&core, 120);
+    /// progress.inc_and_draw(&build, 1);
+    /// ```
+    pub fn child_bar<S: Into<String>>(&mut self, parent: &Bar, total: usize, label: S) -> Bar {
+        let idx = self.index(parent);
+        let p = &mut self.bars[idx];
+        p.children += 1;
+
+        let mut bar = SubBar::new(Some(total), label.into());
+        bar.parent = Some(idx);
+        bar.depth = p.depth + 1;
+        self.push_bar(bar)
+    }
+
+    /// Derive the progress and total of a [`Bar`] from those of its children,
+    /// including any created later.
+    ///
+    /// As with [`Progress::aggregate`], cancelled children count as complete,
+    /// and the parent's progress can no longer be set by hand.
+    ///
+    /// ```
+    /// use linya::Progress;
+    ///
+    /// let mut progress = Progress::new();
+    /// let download = progress.indeterminate("Download");
+    /// progress.sum_children(&download);
+    ///
+    /// let a = progress.child_bar(&download, 1024, "a.tar.gz");
+    /// let b = progress.child_bar(&download, 2048, "b.tar.gz");
+    /// progress.inc_and_draw(&a, 1024);
+    /// ```
+    pub fn sum_children(&mut self, parent: &Bar) {
+        let idx = self.index(parent);
+        let b = &mut self.bars[idx];
+
+        if b.source.is_none() {
+            b.source = Some(Source::Children);
+            b.dirty = true;
+            self.aggregates.push(idx);
+        }
+    }
+
+    /// Render a new bar in its initial empty state and start tracking it.
+    fn push_bar(&mut self, bar: SubBar) -> Bar {
+        let now = Instant::now();
 
-        let bar = SubBar {
-            curr: 0,
-            prev_percent: 0,
-            total,
-            label,
-            cancelled: false,
-        };
         self.bars.push(bar);
-        Bar(self.bars.len() - 1)
+        let idx = self.bars.len() - 1;
+
+        if let Some(events) = self.events.as_mut() {
+            let b = &self.bars[idx];
+            events.emit(Event::Created {
+                bar: idx,
+                label: &b.label,
+                total: b.total,
+                parent: b.parent,
+            });
+        }
+
+        if self.bars[idx].source.is_some() {
+            self.aggregates.push(idx);
+            self.derive(idx);
+        }
+
+        // New bars otherwise go at the very bottom.
+        let pinned = self.bars[idx].pin.is_some()
+            || self.bars[idx].parent.is_some()
+            || self
+                .aggregates
+                .iter()
+                .any(|a| self.bars[*a].pin == Some(Pin::Bottom));
+
+        match self.size {
+            // Once the screen is full, the bars are arranged anew to make room.
+            Some((_, h)) if pinned || self.folded.is_some() || self.lines + 1 >= h => {
+                self.relayout(self.lines, h);
+            }
+            Some((w, _)) => {
+                self.bars[idx].row = Some(self.lines);
+                self.lines += 1;
+
+                // An initial "empty" rendering of the new bar.
+                self.render(idx, w, now, true);
+                let _ = writeln!(self.out, "{}", self.line);
+            }
+            None => self.report(idx, now, true),
+        }
+
+        // The overall progress has another bar to it now.
+        self.draw_mirror();
+        let _ = self.out.flush();
+
+        self.handle(idx)
     }
 
     /// Set a particular [`Bar`]'s progress value, but don't draw it.
     pub fn set(&mut self, bar: &Bar, value: usize) {
-        self.bars[bar.0].curr = value;
+        let idx = self.index(bar);
+        let b = &mut self.bars[idx];
+        b.counter.store(value, Ordering::Relaxed);
+        b.observe(Instant::now());
+    }
+
+    /// Set the target total of a [`Bar`], but don't draw it.
+    ///
+    /// This is how an [indeterminate](Progress::indeterminate) bar is turned
+    /// into a regular one, once its total is known.
+    pub fn set_total(&mut self, bar: &Bar, total: usize) {
+        let idx = self.index(bar);
+        let b = &mut self.bars[idx];
+
+        if b.total != Some(total) {
+            b.total = Some(total);
+            b.dirty = true;
+            b.observe(Instant::now());
+        }
+    }
+
+    /// Show a status message next to the label of a particular [`Bar`], like
+    /// the file currently being worked on. An empty message removes it.
+    ///
+    /// ```
+    /// use linya::Progress;
+    ///
+    /// let mut progress = Progress::new();
+    /// let bar = progress.bar(5, "Uploading");
+    /// progress.set_message(&bar, "retrying 2/5");
+    /// progress.draw(&bar);
+    /// ```
+    ///
+    /// The message is cut short to fit the space left beside the label, so it
+    /// never pushes the bar out of place. With a [`Template`], it is shown
+    /// wherever the `{msg}` key is given.
+    pub fn set_message<S: Into<String>>(&mut self, bar: &Bar, message: S) {
+        let mut message = message.into();
+
+        // Line breaks, tabs and escape codes would all throw off the layout and
+        // the cursor movements of later draws.
+        if message.contains(char::is_control) {
+            message = message.replace(char::is_control, " ");
+        }
+
+        let idx = self.index(bar);
+        let b = &mut self.bars[idx];
+        b.message = message;
+        b.dirty = true;
+    }
+
+    /// Lay out a particular [`Bar`] according to the given [`Template`],
+    /// instead of that of its `Progress`.
+    pub fn set_template(&mut self, bar: &Bar, template: Template) {
+        let idx = self.index(bar);
+        let b = &mut self.bars[idx];
+        b.template = Some(template);
+        b.dirty = true;
+    }
+
+    /// Draw a particular [`Bar`] with the given [`BarStyle`], instead of that
+    /// of its `Progress`.
+    pub fn set_style(&mut self, bar: &Bar, style: BarStyle) {
+        let idx = self.index(bar);
+        let b = &mut self.bars[idx];
+        b.style = Some(style);
+        b.dirty = true;
+    }
+
+    /// Paint a particular [`Bar`] with the given [`Colors`], instead of those
+    /// of its `Progress`.
+    pub fn set_colors(&mut self, bar: &Bar, colors: Colors) {
+        let idx = self.index(bar);
+        let b = &mut self.bars[idx];
+        b.colors = Some(colors);
+        b.dirty = true;
+    }
+
+    /// Decide what becomes of a particular [`Bar`] once it has finished,
+    /// instead of following the policy of its `Progress`.
+    pub fn set_on_finish(&mut self, bar: &Bar, on_finish: OnFinish) {
+        let idx = self.index(bar);
+        let b = &mut self.bars[idx];
+        b.on_finish = Some(on_finish);
+        b.dirty = true;
+    }
+
+    /// Show the progress of a particular [`Bar`] in the terminal's tab or
+    /// title, rather than the overall progress. See [`Progress::with_mirror`].
+    pub fn mirror_bar(&mut self, bar: &Bar) {
+        self.mirrored = Some(self.index(bar));
+        self.mirror_dirty = true;
+    }
+
+    /// Obtain a [`SharedBar`] handle to the given [`Bar`], whose progress can
+    /// be updated from any thread without locking this `Progress`.
+    ///
+    /// Drawing is left to the coordinator, usually via a [`Ticker`].
+    pub fn share(&self, bar: &Bar) -> SharedBar {
+        SharedBar::new(bar.dup(), self.bars[self.index(bar)].counter.clone())
+    }
+
+    /// The widths of the label and of the bar itself, given the terminal width.
+    fn widths(&self, term_width: usize) -> (usize, usize) {
+        let avail = if self.rates {
+            term_width.saturating_sub(RATES_WIDTH)
+        } else {
+            term_width
+        };
+        let w = (avail / 2).saturating_sub(7).max(1);
+        let l = avail.saturating_sub(w + 8 + 5);
+
+        (l, w)
     }
 
     /// Force the drawing of a particular [`Bar`].
     ///
     /// **Note 1:** Drawing will only occur if there is something meaningful to
     /// show. Namely, if the progress has advanced at least 1% since the last
-    /// draw.
+    /// draw, or if [rates](Progress::with_rates) are shown and a second has
+    /// passed. [Indeterminate](Progress::indeterminate) bars are redrawn when
+    /// their animation is due for its next frame.
     ///
-    /// **Note 2:** If your program is not being run in a terminal, an initial
-    /// empty bar will be printed but never refreshed.
+    /// **Note 2:** If your program is not being run in a terminal, progress is
+    /// instead reported as [plain](Progress::with_plain) lines of text, which
+    /// are throttled in their own way.
     pub fn draw(&mut self, bar: &Bar) {
+        self.check_size(Instant::now());
         self.draw_impl(bar, false);
 
+        // Parents deriving their progress from this bar, one level at a time.
+        let mut parent = self.bars[self.index(bar)].parent;
+        while let Some(p) = parent {
+            if self.bars[p].source.is_some() {
+                self.draw_impl(&self.handle(p), false);
+            }
+            parent = self.bars[p].parent;
+        }
+
+        for i in 0..self.aggregates.len() {
+            let aggregate = self.aggregates[i];
+
+            if self.bars[aggregate].pin.is_some() && self.tracks(aggregate, bar.0) {
+                self.draw_impl(&self.handle(aggregate), false);
+            }
+        }
+
+        self.draw_mirror();
+
         // Very important, or the output won't appear fluid.
         let _ = self.out.flush();
     }
 
+    /// Try to draw every [`Bar`], flushing the output only once.
+    ///
+    /// The same rules as in [`Progress::draw`] apply to each bar, so only
+    /// those with something new to show are actually redrawn. This is what a
+    /// [`Ticker`] calls on every frame.
+    pub fn draw_all(&mut self) {
+        self.check_size(Instant::now());
+
+        for bar in 0..self.bars.len() {
+            self.draw_impl(&self.handle(bar), false);
+        }
+
+        self.draw_mirror();
+        let _ = self.out.flush();
+    }
+
+    /// Inform the `Progress` of a new terminal size, and redraw all bars to
+    /// fit it.
+    ///
+    /// This normally isn't necessary, since the terminal is measured again
+    /// every so often while drawing, and all bars are laid out anew when its
+    /// size has changed. It is useful however for sizes fixed via
+    /// [`Progress::with_size`], or to react to a `SIGWINCH` straight away.
+    ///
+    /// ```
+    /// use linya::Progress;
+    ///
+    /// let mut progress = Progress::with_writer(Vec::new()).with_size(80, 24);
+    /// let bar = progress.bar(50, "Downloading");
+    /// progress.resize(40, 24);
+    /// ```
+    pub fn resize(&mut self, width: usize, height: usize) {
+        // Without a previous size, nothing has been drawn in place. Otherwise
+        // only the lines that were on screen before, and still are, can be
+        // reached by the cursor.
+        let up = match self.size.replace((width, height)) {
+            Some((_, old_height)) => self.lines.min(old_height.min(height).saturating_sub(1)),
+            None => 0,
+        };
+
+        self.relayout(up, height);
+    }
+
+    /// Move up `up` lines, clear everything below, and draw all bars anew
+    /// after deciding which of them fit on a screen of the given height.
+    fn relayout(&mut self, up: usize, height: usize) {
+        self.arrange(height);
+
+        if up > 0 {
+            let _ = write!(self.out, "\x1B[{}A", up);
+        }
+
+        // Clear everything below, as the old lines may be longer than the new
+        // ones, or more numerous.
+        let _ = write!(self.out, "\r\x1B[J");
+        self.draw_region();
+        let _ = self.out.flush();
+    }
+
+    /// Decide which bars get a line of their own on a screen of the given
+    /// height.
+    ///
+    /// If they don't all fit, a line instead tells how many were folded away.
+    /// Pinned bars are always shown, and active bars before finished ones. The
+    /// most recently created of those that are finished fill any lines to
+    /// spare.
+    fn arrange(&mut self, height: usize) {
+        let now = Instant::now();
+        let on_finish = self.on_finish;
+
+        // Nested bars come after their parents, so are derived first.
+        for i in (0..self.bars.len()).rev() {
+            if self.bars[i].source.is_some() {
+                self.derive(i);
+            }
+        }
+
+        for b in self.bars.iter_mut() {
+            b.observe(now);
+            b.settle(on_finish);
+            b.row = None;
+        }
+
+        let (pinned, free): (Vec<usize>, Vec<usize>) = self
+            .tree_order()
+            .into_iter()
+            .filter(|i| self.visible(*i))
+            .partition(|i| self.bars[*i].pin.is_some());
+        let room = height.saturating_sub(1).saturating_sub(pinned.len());
+        let (mut active, mut done) =
+            free.iter()
+                .fold((0, 0), |(active, done), i| match self.bars[*i].ended {
+                    Some(_) => (active, done + 1),
+                    None => (active + 1, done),
+                });
+
+        // Marking the chosen bars first, and numbering them after.
+        if active + done <= room {
+            self.folded = None;
+
+            for i in free.iter() {
+                self.bars[*i].row = Some(0);
+            }
+        } else if room > 0 {
+            // One line goes to the summary of the others.
+            let slots = room - 1;
+            let mut active_slots = slots.min(active);
+            let mut done_slots = slots - active_slots;
+
+            for i in free.iter() {
+                let b = &mut self.bars[*i];
+
+                if active_slots > 0 && b.ended.is_none() {
+                    b.row = Some(0);
+                    active_slots -= 1;
+                    active -= 1;
+                }
+            }
+
+            for i in free.iter().rev() {
+                let b = &mut self.bars[*i];
+
+                if done_slots > 0 && b.ended.is_some() {
+                    b.row = Some(0);
+                    done_slots -= 1;
+                    done -= 1;
+                }
+            }
+
+            self.folded = Some(Folded {
+                row: 0,
+                more: active + done,
+                done,
+            });
+        } else {
+            self.folded = None;
+        }
+
+        // Bars pinned to the top come first, then the summary, then the
+        // others, and then bars pinned to the bottom.
+        let pin = |pin: Pin| {
+            let bars = &self.bars;
+            pinned
+                .iter()
+                .copied()
+                .filter(move |i| bars[*i].pin == Some(pin))
+        };
+        let lines: Vec<Option<usize>> = pin(Pin::Top)
+            .map(Some)
+            .chain(self.folded.map(|_| None))
+            .chain(
+                free.iter()
+                    .copied()
+                    .filter(|i| self.bars[*i].row.is_some())
+                    .map(Some),
+            )
+            .chain(pin(Pin::Bottom).map(Some))
+            .collect();
+
+        for (row, line) in lines.iter().enumerate() {
+            match (line, self.folded.as_mut()) {
+                (Some(i), _) => self.bars[*i].row = Some(row),
+                (None, Some(folded)) => folded.row = row,
+                (None, None) => {}
+            }
+        }
+
+        self.lines = lines.len();
+    }
+
+    /// The indices of all bars, with each followed by those nested under it.
+    fn tree_order(&self) -> Vec<usize> {
+        let mut children = vec![vec![]; self.bars.len()];
+        let mut stack = vec![];
+
+        for (i, b) in self.bars.iter().enumerate().rev() {
+            match b.parent {
+                Some(p) => children[p].push(i),
+                None => stack.push(i),
+            }
+        }
+
+        // Both lists are in reverse, for popping off the stack in order.
+        let mut order = Vec::with_capacity(self.bars.len());
+        while let Some(i) = stack.pop() {
+            order.push(i);
+            stack.append(&mut children[i]);
+        }
+
+        order
+    }
+
+    /// Could the bar be shown at all? It can't once cleared away, nor once
+    /// collapsed into a finished parent.
+    fn visible(&self, bar: usize) -> bool {
+        let b = &self.bars[bar];
+
+        !b.cleared
+            && b.parent
+                .map(|p| self.bars[p].ended.is_none() && self.visible(p))
+                .unwrap_or(true)
+    }
+
+    /// Draw the summary of any folded bars and every bar with a line of its
+    /// own, in order, at the cursor.
+    fn draw_region(&mut self) {
+        let mut order: Vec<(usize, Option<usize>)> = self
+            .bars
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.row.map(|row| (row, Some(i))))
+            .chain(self.folded.map(|f| (f.row, None)))
+            .collect();
+        order.sort_unstable();
+
+        for (_, bar) in order {
+            match (bar, self.folded, self.size) {
+                (Some(bar), _, _) => self.draw_impl(&self.handle(bar), true),
+                (None, Some(folded), Some((term_width, _))) => {
+                    self.line.clear();
+                    render::folded(&mut self.line, folded.more, folded.done);
+                    render::truncate(&mut self.line, 0, term_width);
+                    let _ = writeln!(self.out, "{}\x1B[K", self.line);
+                }
+                (None, _, _) => {}
+            }
+        }
+    }
+
+    /// Update an aggregate bar from the bars it tracks.
+    fn derive(&mut self, bar: usize) {
+        let (curr, total) = self.sums(bar);
+        let b = &mut self.bars[bar];
+        b.counter.store(curr, Ordering::Relaxed);
+
+        if b.total != total {
+            b.total = total;
+            b.dirty = true;
+        }
+    }
+
+    /// The combined progress and total of the bars tracked by an aggregate
+    /// bar. Cancelled bars count as complete, and the total is unknown while
+    /// that of any other bar is.
+    fn sums(&self, bar: usize) -> (usize, Option<usize>) {
+        self.sums_by(|i| self.tracks(bar, i))
+    }
+
+    /// The combined progress and total of the bars picked by the given
+    /// predicate, counted as in [`Progress::sums`].
+    fn sums_by<F: Fn(usize) -> bool>(&self, picked: F) -> (usize, Option<usize>) {
+        let (curr, total, any) = self
+            .bars
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| picked(*i))
+            .fold((0, Some(0), false), |(curr, total, _), (_, b)| {
+                let value = b.counter.load(Ordering::Relaxed);
+                let (value, t) = match b.total {
+                    _ if b.cancelled => {
+                        let t = b.total.unwrap_or(value);
+                        (t, Some(t))
+                    }
+                    t => (value, t),
+                };
+
+                (curr + value, total.and_then(|n| t.map(|t| n + t)), true)
+            });
+
+        // With nothing to track yet, there is no total to speak of.
+        (curr, total.filter(|_| any))
+    }
+
+    /// Does the given aggregate bar track the other bar?
+    fn tracks(&self, aggregate: usize, bar: usize) -> bool {
+        match &self.bars[aggregate].source {
+            Some(Source::All) => {
+                let b = &self.bars[bar];
+                bar != aggregate && b.source.is_none() && b.children == 0
+            }
+            Some(Source::Children) => self.bars[bar].parent == Some(aggregate),
+            Some(Source::Group(bars)) => bars.contains(&bar),
+            None => false,
+        }
+    }
+
+    /// Show the latest progress in the terminal's tab or title, if it has
+    /// changed, or clear it away once all is done.
+    fn draw_mirror(&mut self) {
+        // Plain lines are likely headed for a log, not a terminal.
+        let mirror = match self.mirror {
+            Some(mirror) if self.size.is_some() => mirror,
+            _ => return,
+        };
+
+        let (curr, total, cancelled, label) = match self.mirrored {
+            Some(bar) => {
+                let b = &self.bars[bar];
+                let (curr, total) = match b.source {
+                    Some(_) => self.sums(bar),
+                    None => (b.counter.load(Ordering::Relaxed), b.total),
+                };
+                (curr, total, b.cancelled, Some(b.label.as_str()))
+            }
+            None => {
+                let (curr, total) =
+                    self.sums_by(|i| self.bars[i].source.is_none() && self.bars[i].children == 0);
+                (curr, total, false, None)
+            }
+        };
+
+        // With no bars at all, there is nothing to show.
+        let idle = self.bars.is_empty();
+        let finished = cancelled || total.map(|t| curr >= t).unwrap_or(false);
+
+        if idle || finished {
+            self.clear_mirror();
+            return;
+        }
+
+        let percent =
+            total.map(|t| (100 * curr as u64).checked_div(t as u64).unwrap_or(100) as usize);
+        if self.shown == Some(percent) && !self.mirror_dirty {
+            return;
+        }
+
+        match (mirror, percent) {
+            (Mirror::Taskbar, Some(p)) => {
+                let _ = write!(self.out, "\x1B]9;4;1;{}\x1B\\", p);
+            }
+            (Mirror::Taskbar, None) => {
+                let _ = write!(self.out, "\x1B]9;4;3;0\x1B\\");
+            }
+            (Mirror::Title, _) => {
+                // Keep the original title, to restore it afterwards.
+                if self.shown.is_none() {
+                    let _ = write!(self.out, "\x1B[22;2t");
+                }
+
+                let _ = write!(self.out, "\x1B]2;");
+                if let Some(p) = percent {
+                    let _ = write!(self.out, "{}%", p);
+                }
+                if let Some(label) = label {
+                    let space = if percent.is_some() { " " } else { "" };
+                    let _ = write!(self.out, "{}{}", space, label);
+                }
+                let _ = write!(self.out, "\x1B\\");
+            }
+        }
+
+        self.shown = Some(percent);
+        self.mirror_dirty = false;
+    }
+
+    /// Remove any progress shown in the terminal's tab or title.
+    fn clear_mirror(&mut self) {
+        if self.shown.take().is_some() {
+            match self.mirror {
+                Some(Mirror::Taskbar) => {
+                    let _ = write!(self.out, "\x1B]9;4;0;0\x1B\\");
+                }
+                Some(Mirror::Title) => {
+                    // Terminals without a stack of titles to pop are at least
+                    // left without the stale progress.
+                    let _ = write!(self.out, "\x1B]2;\x1B\\\x1B[23;2t");
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Measure the terminal again if it's been a while, and resize if it has
+    /// changed.
+    fn check_size(&mut self, now: Instant) {
+        if let Some(measured) = self.measured {
+            if now.saturating_duration_since(measured) >= RESIZE_CHECK {
+                self.measured = Some(now);
+
+                match measure() {
+                    Some((w, h)) if self.size != Some((w, h)) => self.resize(w, h),
+                    _ => {}
+                }
+            }
+        }
+    }
+
     /// Actually draw a particular [`Bar`].
     ///
     /// When `force` is true draw the bar at the current cursor position and
@@ -202,51 +1282,75 @@ impl Progress {
     /// This function does not flush the output stream.
     fn draw_impl(&mut self, bar: &Bar, force: bool) {
         // If there is no legal width value present, that means we aren't
-        // running in a terminal, and no rerendering can be done.
+        // running in a terminal, and no rerendering can be done. Progress is
+        // reported as plain text instead.
         if let Some((term_width, term_height)) = self.size {
-            let pos = self.bars.len() - bar.0;
-            let mut b = &mut self.bars[bar.0];
-            let cur_percent = (100 * b.curr as u64) / (b.total as u64);
-            // For a newly cancelled bar `diff` is equal to 100.
-            let diff = cur_percent - b.prev_percent as u64;
-
-            // For now, if the progress for a particular bar is slow and drifts
-            // past the top of the terminal, redrawing is paused.
-            if (pos < term_height && diff >= 1) || force {
-                let w = (term_width / 2) - 7;
-                let (data, unit) = denomination(b.curr);
-                b.prev_percent = cur_percent as usize;
+            let now = Instant::now();
+
+            if self.bars[self.index(bar)].source.is_some() {
+                self.derive(bar.0);
+            }
+
+            let idx = self.index(bar);
+            self.bars[idx].observe(now);
+            self.announce(idx, now);
+
+            let b = &mut self.bars[idx];
+
+            // A bar that finished may have to make way for another, or for
+            // nothing at all.
+            if !force
+                && b.settle(self.on_finish)
+                && (b.cleared || b.children > 0 || self.folded.is_some())
+            {
+                self.relayout(self.lines, term_height);
+                return;
+            }
+
+            // Bars that were folded or cleared away have nowhere to be drawn.
+            let row = match b.row {
+                Some(row) => row,
+                None => return,
+            };
+
+            let pos = self.lines - row;
+            let since = now.saturating_duration_since(b.drawn);
+            let (cur_percent, changed) = match b.total {
+                Some(total) => {
+                    let cur_percent = (100 * b.curr as u64)
+                        .checked_div(total as u64)
+                        .unwrap_or(100) as usize;
+                    (cur_percent, cur_percent != b.prev_percent)
+                }
+                // An indeterminate bar changes with every frame of animation.
+                None => (0, since >= SPIN_INTERVAL),
+            };
+            let refresh = match b.template.as_ref().or(self.template.as_ref()) {
+                Some(template) => template.refresh(),
+                None if self.rates => Some(RATES_REFRESH),
+                None => None,
+            };
+            // Are the clocks or animations of an ongoing bar out of date?
+            let stale = b.ended.is_none() && refresh.map(|r| since >= r).unwrap_or(false);
+
+            if changed || stale || b.dirty || force {
+                b.prev_percent = cur_percent;
+                b.drawn = now;
+                b.dirty = false;
+                self.render(bar.0, term_width, now, false);
 
                 if !force {
                     // Save cursor position and then move up `pos` lines.
                     let _ = write!(self.out, "\x1B[s\x1B[{}A\r", pos);
                 }
 
-                let _ = write!(
-                    self.out,
-                    "{:<l$} {:3}{} [",
-                    b.label,
-                    data,
-                    unit,
-                    l = term_width - w - 8 - 5,
-                );
-                if b.cancelled {
-                    let _ = write!(self.out, "{:_>f$}] ??? ", "", f = w);
-                } else if b.curr >= b.total {
-                    let _ = write!(self.out, "{:#>f$}] 100%", "", f = w);
-                } else {
-                    let f = (((w as u64) * (b.curr as u64) / (b.total as u64)) as usize).min(w - 1);
-                    let e = (w - 1) - f;
-
-                    let _ = write!(
-                        self.out,
-                        "{:#>f$}>{:->e$}] {:3}%",
-                        "",
-                        "",
-                        (100 * (b.curr as u64)) / (b.total as u64),
-                        f = f,
-                        e = e
-                    );
+                let _ = self.out.write_all(self.line.as_bytes());
+
+                // Erase what's left of a longer line drawn here before, like a
+                // bar replaced by its summary. A full line is left alone, as
+                // the cursor may rest on its last character.
+                if render::width(&self.line, 0) < term_width {
+                    let _ = write!(self.out, "\x1B[K");
                 }
 
                 if !force {
@@ -256,9 +1360,132 @@ impl Progress {
                     let _ = writeln!(self.out);
                 }
             }
+        } else {
+            self.report(self.index(bar), Instant::now(), force);
         }
     }
 
+    /// Report a particular bar as a plain line of text, if it has advanced by
+    /// another step, made any progress at all in a while, or just finished.
+    ///
+    /// When `force` is true the line is written regardless.
+    fn report(&mut self, bar: usize, now: Instant, force: bool) {
+        if self.bars[bar].source.is_some() {
+            self.derive(bar);
+        }
+
+        self.bars[bar].observe(now);
+        self.announce(bar, now);
+
+        let step = self.plain.step;
+        let b = &mut self.bars[bar];
+
+        let finished = b.settle(self.on_finish) && b.ended.is_some();
+        let percent = b.total.map(|total| {
+            (100 * b.curr as u64)
+                .checked_div(total as u64)
+                .unwrap_or(100) as usize
+        });
+        // Finished bars have already said their last.
+        let going = b.ended.is_none();
+        let stepped = percent
+            .map(|p| p / step != b.prev_percent / step)
+            .unwrap_or(false);
+        let waited =
+            now.saturating_duration_since(b.drawn) >= self.plain.interval && b.curr != b.reported;
+
+        if force || finished || (going && (stepped || waited)) {
+            b.prev_percent = percent.unwrap_or(0);
+            b.drawn = now;
+            b.reported = b.curr;
+
+            let s = &mut self.line;
+            s.clear();
+
+            match b.on_finish.unwrap_or(self.on_finish) {
+                OnFinish::Summarize if finished => {
+                    let look = Look {
+                        style: &self.style,
+                        colors: None,
+                    };
+                    render::summary(s, b, look, now);
+                }
+                _ => render::plain(s, b),
+            }
+
+            let _ = writeln!(self.out, "{}", self.line);
+        }
+    }
+
+    /// Emit the events of a particular bar that are due, if anyone is listening:
+    /// its progress, when it has advanced far enough or for long enough, and
+    /// its finishing.
+    fn announce(&mut self, bar: usize, now: Instant) {
+        if let Some(events) = self.events.as_mut() {
+            let b = &mut self.bars[bar];
+            let ended = b.ended.is_some();
+
+            // A cancelled bar is only filled for show, so its progress stands
+            // where it was.
+            if b.curr != b.emitted && !b.cancelled {
+                let total = b.total;
+                let percent = |v: usize| total.and_then(|t| (100 * v as u64).checked_div(t as u64));
+                let due = ended
+                    || percent(b.curr) != percent(b.emitted)
+                    || now.saturating_duration_since(b.emitted_at) >= EVENT_INTERVAL;
+
+                if due {
+                    b.emitted = b.curr;
+                    b.emitted_at = now;
+                    events.emit(Event::Progress {
+                        bar,
+                        value: b.curr,
+                        total,
+                    });
+                }
+            }
+
+            if ended != b.announced {
+                b.announced = ended;
+
+                match b.cancelled {
+                    _ if !ended => {}
+                    true => events.emit(Event::Cancelled {
+                        bar,
+                        value: b.halted,
+                    }),
+                    false => events.emit(Event::Finished { bar, value: b.curr }),
+                }
+            }
+        }
+    }
+
+    /// Render the line of a particular bar into `self.line`.
+    fn render(&mut self, bar: usize, term_width: usize, now: Instant, initial: bool) {
+        let b = &self.bars[bar];
+        let indent = 2 * b.depth;
+        let term_width = term_width.saturating_sub(indent);
+        let (l, w) = self.widths(term_width);
+        let look = Look {
+            style: b.style.as_ref().unwrap_or(&self.style),
+            colors: Some(b.colors.as_ref().unwrap_or(&self.colors)).filter(|_| self.colored),
+        };
+        let s = &mut self.line;
+        s.clear();
+        render::fill(s, ' ', indent);
+        let summarize = b.on_finish.unwrap_or(self.on_finish) == OnFinish::Summarize;
+
+        match b.template.as_ref().or(self.template.as_ref()) {
+            _ if summarize && b.ended.is_some() => render::summary(s, b, look, now),
+            Some(template) => template.render(s, b, look, term_width, now),
+            None if initial => render::initial(s, b, look, l, w, self.rates),
+            None => render::line(s, b, look, l, w, self.rates, now),
+        }
+
+        // A line that wraps would throw off the cursor movements of later draws.
+        render::truncate(s, 0, term_width + indent);
+    }
+
     /// Set a [`Bar`]'s value and immediately try to draw it.
     pub fn set_and_draw(&mut self, bar: &Bar, value: usize) {
         self.set(bar, value);
@@ -267,7 +1494,10 @@ impl Progress {
 
     /// Increment a given [`Bar`]'s progress, but don't draw it.
     pub fn inc(&mut self, bar: &Bar, value: usize) {
-        self.set(bar, self.bars[bar.0].curr + value)
+        let idx = self.index(bar);
+        let b = &mut self.bars[idx];
+        b.counter.fetch_add(value, Ordering::Relaxed);
+        b.observe(Instant::now());
     }
 
     /// Increment a given [`Bar`]'s progress and immediately try to draw it.
@@ -277,9 +1507,118 @@ impl Progress {
     }
 
     /// Has the given bar completed?
+    ///
+    /// An [indeterminate](Progress::indeterminate) bar is never considered
+    /// complete.
     pub fn is_done(&self, bar: &Bar) -> bool {
-        let b = &self.bars[bar.0];
-        b.curr >= b.total
+        let b = &self.bars[self.index(bar)];
+        let (curr, total) = match b.source {
+            Some(_) => self.sums(self.index(bar)),
+            None => (b.counter.load(Ordering::Relaxed), b.total),
+        };
+        total.map(|t| curr >= t).unwrap_or(false)
+    }
+
+    /// Like [`Progress::set`], but fail instead of panicking on a foreign
+    /// [`Bar`], and refuse to move a bar that has already finished.
+    ///
+    /// ```
+    /// use linya::{Error, Progress};
+    ///
+    /// let mut progress = Progress::new();
+    /// let bar = progress.bar(10, "Downloading");
+    /// assert_eq!(Ok(()), progress.try_set(&bar, 10));
+    /// assert_eq!(Err(Error::Finished), progress.try_set(&bar, 5));
+    /// ```
+    pub fn try_set(&mut self, bar: &Bar, value: usize) -> Result<(), Error> {
+        self.check_live(bar)?;
+        self.set(bar, value);
+        Ok(())
+    }
+
+    /// Like [`Progress::set_and_draw`], but checked as with
+    /// [`Progress::try_set`].
+    pub fn try_set_and_draw(&mut self, bar: &Bar, value: usize) -> Result<(), Error> {
+        self.try_set(bar, value)?;
+        self.draw(bar);
+        Ok(())
+    }
+
+    /// Like [`Progress::inc`], but checked as with [`Progress::try_set`].
+    pub fn try_inc(&mut self, bar: &Bar, value: usize) -> Result<(), Error> {
+        self.check_live(bar)?;
+        self.inc(bar, value);
+        Ok(())
+    }
+
+    /// Like [`Progress::inc_and_draw`], but checked as with
+    /// [`Progress::try_set`].
+    pub fn try_inc_and_draw(&mut self, bar: &Bar, value: usize) -> Result<(), Error> {
+        self.try_inc(bar, value)?;
+        self.draw(bar);
+        Ok(())
+    }
+
+    /// Like [`Progress::set_total`], but checked as with [`Progress::try_set`].
+    pub fn try_set_total(&mut self, bar: &Bar, total: usize) -> Result<(), Error> {
+        self.check_live(bar)?;
+        self.set_total(bar, total);
+        Ok(())
+    }
+
+    /// Like [`Progress::cancel`], but fail instead of panicking on a foreign
+    /// [`Bar`], and leave a bar be that has already finished.
+    ///
+    /// ```
+    /// use linya::{Error, Progress};
+    ///
+    /// let mut progress = Progress::new();
+    /// let bar = progress.bar(10, "Downloading");
+    /// progress.set(&bar, 10);
+    /// assert_eq!(Err(Error::Finished), progress.try_cancel(bar));
+    /// ```
+    pub fn try_cancel(&mut self, bar: Bar) -> Result<(), Error> {
+        self.check_live(&bar)?;
+        self.cancel(bar);
+        Ok(())
+    }
+
+    /// Like [`Progress::is_done`], but fail instead of panicking on a foreign
+    /// [`Bar`].
+    pub fn try_is_done(&self, bar: &Bar) -> Result<bool, Error> {
+        self.check(bar)?;
+        Ok(self.is_done(bar))
+    }
+
+    /// A handle to the bar at the given index.
+    fn handle(&self, idx: usize) -> Bar {
+        Bar(idx, self.id)
+    }
+
+    /// The index of the given bar, which had better be one of ours.
+    fn index(&self, bar: &Bar) -> usize {
+        debug_assert_eq!(
+            self.id, bar.1,
+            "Bar used with a Progress that didn't make it"
+        );
+        bar.0
+    }
+
+    /// Does this `Progress` have the given bar?
+    fn check(&self, bar: &Bar) -> Result<&SubBar, Error> {
+        self.bars
+            .get(bar.0)
+            .filter(|_| self.id == bar.1)
+            .ok_or(Error::ForeignBar)
+    }
+
+    /// Does this `Progress` have the given bar, and is it still running?
+    fn check_live(&self, bar: &Bar) -> Result<(), Error> {
+        if self.check(bar)?.cancelled || self.is_done(bar) {
+            Err(Error::Finished)
+        } else {
+            Ok(())
+        }
     }
 
     /// Cancel the given bar, say in the case of download failure, etc.
@@ -287,13 +1626,13 @@ impl Progress {
     /// This fills the bar with the "cancel" character and consumes `Bar`
     /// ownership so that the bar cannot be manipulated again.
     pub fn cancel(&mut self, bar: Bar) {
-        {
-            let mut b = &mut self.bars[bar.0];
-            b.cancelled = true;
-            // Force redraw by setting `prev_percent` to 0.
-            b.prev_percent = 0;
-        }
-        self.set_and_draw(&bar, self.bars[bar.0].total);
+        let idx = self.index(&bar);
+        let b = &mut self.bars[idx];
+        b.cancelled = true;
+        b.dirty = true;
+        b.halted = b.counter.load(Ordering::Relaxed);
+        let value = b.total.unwrap_or(b.curr);
+        self.set_and_draw(&bar, value);
     }
 
     /// Return a handle to write above all progress bars.
@@ -311,20 +1650,45 @@ impl Progress {
     /// ```
     pub fn stderr(&mut self) -> impl fmt::Write + '_ {
         // Move to first line of the progress bars, erase the complete line and print the message.
-        let _ = write!(self.out, "\x1B[{}A\x1B[2K\r", self.bars.len()).map_err(|_| fmt::Error);
-        WriteHandle { prog: self }
+        if self.lines > 0 {
+            let _ = write!(self.out, "\x1B[{}A", self.lines);
+        }
+        // Plain lines are only ever appended to.
+        if self.size.is_some() {
+            let _ = write!(self.out, "\x1B[2K\r");
+        }
+        WriteHandle {
+            prog: self,
+            message: String::new(),
+        }
+    }
+}
+
+/// The terminal's tab or title is left as it was found.
+impl<W: Write> Drop for Progress<W> {
+    fn drop(&mut self) {
+        if self.shown.is_some() {
+            self.clear_mirror();
+            let _ = self.out.flush();
+        }
     }
 }
 
 /// A write handle that exclusively holds a [`Progress`] instance so
 /// that no draws can interfere with writing.
 #[derive(Debug)]
-struct WriteHandle<'a> {
-    prog: &'a mut Progress,
+struct WriteHandle<'a, W: Write> {
+    prog: &'a mut Progress<W>,
+    /// Everything written so far, to be sent as an event once done.
+    message: String,
 }
 
-impl<'a> fmt::Write for WriteHandle<'a> {
+impl<'a, W: Write> fmt::Write for WriteHandle<'a, W> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.prog.events.is_some() {
+            self.message.push_str(s);
+        }
+
         self.prog
             .out
             .write_all(s.as_bytes())
@@ -332,44 +1696,240 @@ impl<'a> fmt::Write for WriteHandle<'a> {
     }
 }
 
-impl<'a> Drop for WriteHandle<'a> {
+impl<'a, W: Write> Drop for WriteHandle<'a, W> {
     fn drop(&mut self) {
-        // Determine first bar that fits on screen.
-        let start = self
-            .prog
-            .size
-            .map(|(_w, h)| {
-                if self.prog.bars.len() >= h {
-                    self.prog.bars.len() - (h - 1)
-                } else {
-                    0
-                }
-            })
-            .unwrap_or_default();
+        if let Some(events) = self.prog.events.as_mut() {
+            let text = self.message.trim_end_matches('\n');
+            events.emit(Event::Message { text });
+        }
 
         // Redraw all progress bars.
-        for bar in start..self.prog.bars.len() {
-            self.prog.draw_impl(&Bar(bar), true);
-        }
+        self.prog.draw_region();
+        self.prog.draw_mirror();
 
         // Flush all of them at once to reduce stutter.
         let _ = self.prog.out.flush();
     }
 }
 
+/// The current size of the terminal, if there is one.
+///
+/// This is the terminal of `stderr`, where bars are drawn, and not that of
+/// `stdout`, which might well be redirected to a file.
+fn measure() -> Option<(usize, usize)> {
+    stderr_size().map(|(Width(w), Height(h))| (w as usize, h as usize))
+}
+
+#[cfg(unix)]
+fn stderr_size() -> Option<(Width, Height)> {
+    use std::os::unix::io::AsRawFd;
+    terminal_size::terminal_size_using_fd(std::io::stderr().as_raw_fd())
+}
+
+#[cfg(windows)]
+fn stderr_size() -> Option<(Width, Height)> {
+    use std::os::windows::io::AsRawHandle;
+    terminal_size::terminal_size_using_handle(std::io::stderr().as_raw_handle())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn stderr_size() -> Option<(Width, Height)> {
+    None
+}
+
 /// An internal structure that stores individual bar state.
 #[derive(Debug)]
 struct SubBar {
     /// Progress as of the previous draw in percent.
     prev_percent: usize,
-    /// Current progress.
+    /// Current progress, shared with any [`SharedBar`] handles.
+    counter: Arc<AtomicUsize>,
+    /// The value of `counter` as last observed.
     curr: usize,
-    /// The progress target.
-    total: usize,
+    /// The progress target, if known.
+    total: Option<usize>,
     /// A user-supplied label for the left side of the bar line.
     label: String,
+    /// A status message shown after the label, if not empty.
+    message: String,
     /// Did the user force this bar to stop?
     cancelled: bool,
+    /// Must the next draw occur, regardless of progress?
+    dirty: bool,
+    /// A layout overriding that of the `Progress`.
+    template: Option<Template>,
+    /// A look overriding that of the `Progress`.
+    style: Option<BarStyle>,
+    /// Colours overriding those of the `Progress`.
+    colors: Option<Colors>,
+    /// A policy overriding that of the `Progress`.
+    on_finish: Option<OnFinish>,
+    /// The line of the bar, counting from the first, or `None` if it doesn't
+    /// have one.
+    row: Option<usize>,
+    /// Was the bar cleared away for good, after finishing?
+    cleared: bool,
+    /// Has the bar's finishing, or lack thereof, been accounted for?
+    settled: bool,
+    /// Where the bar is pinned, if anywhere.
+    pin: Option<Pin>,
+    /// The bars whose progress is summed up by this one, if any.
+    source: Option<Source>,
+    /// The bar this one is nested under, if any.
+    parent: Option<usize>,
+    /// How deeply the bar is nested.
+    depth: usize,
+    /// The number of bars nested directly under this one.
+    children: usize,
+    /// When the bar was created.
+    started: Instant,
+    /// When the bar reached its total, if it has.
+    ended: Option<Instant>,
+    /// When the bar was last drawn.
+    drawn: Instant,
+    /// The progress of the bar as last reported in plain text.
+    reported: usize,
+    /// The progress of the bar as last reported as an event.
+    emitted: usize,
+    /// When the progress of the bar was last reported as an event.
+    emitted_at: Instant,
+    /// Has the bar's finishing been reported as an event?
+    announced: bool,
+    /// The progress a cancelled bar had actually made, before being filled.
+    halted: usize,
+    /// Recent progress, for measuring throughput.
+    rate: Rate,
+}
+
+impl SubBar {
+    /// A bar with nothing done yet.
+    fn new(total: Option<usize>, label: String) -> SubBar {
+        let now = Instant::now();
+
+        SubBar {
+            counter: Arc::new(AtomicUsize::new(0)),
+            curr: 0,
+            prev_percent: 0,
+            total,
+            label,
+            message: String::new(),
+            cancelled: false,
+            dirty: false,
+            template: None,
+            style: None,
+            colors: None,
+            on_finish: None,
+            row: None,
+            cleared: false,
+            settled: false,
+            pin: None,
+            source: None,
+            parent: None,
+            depth: 0,
+            children: 0,
+            started: now,
+            ended: None,
+            drawn: now,
+            reported: 0,
+            emitted: 0,
+            emitted_at: now,
+            announced: false,
+            halted: 0,
+            rate: Rate::new(now),
+        }
+    }
+
+    /// Catch up with the latest value of the shared counter, which may have
+    /// been changed by a [`SharedBar`] in the meantime.
+    fn observe(&mut self, now: Instant) {
+        let value = self.counter.load(Ordering::Relaxed);
+        let finished = self.cancelled || self.total.map(|t| value >= t).unwrap_or(false);
+
+        self.curr = value;
+        self.rate.record(now, value);
+        self.ended = match self.ended {
+            Some(_) if finished => self.ended,
+            _ if finished => Some(now),
+            _ => None,
+        };
+    }
+
+    /// Account for a change in whether the bar has finished, clearing it if
+    /// its policy says so. Returns `true` if anything changed.
+    fn settle(&mut self, default: OnFinish) -> bool {
+        let finished = self.ended.is_some();
+
+        if finished == self.settled {
+            return false;
+        }
+
+        self.settled = finished;
+        self.cleared |= finished && self.on_finish.unwrap_or(default) == OnFinish::Clear;
+        true
+    }
+}
+
+/// What becomes of a bar once it has finished, either by reaching its total or
+/// by being cancelled.
+///
+/// Set a policy for all bars with [`Progress::with_on_finish`], or for a single
+/// bar with [`Progress::set_on_finish`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OnFinish {
+    /// Leave the full bar in place. This is the default.
+    #[default]
+    Leave,
+    /// Remove the bar, moving the bars below it up to fill its line. Cleared
+    /// bars are never drawn again.
+    Clear,
+    /// Replace the bar with a one-line summary, like
+    /// `✓ Downloading 3 — 12M in 4.2s`.
+    Summarize,
+}
+
+/// Where a bar is pinned in relation to the others. See
+/// [`Progress::aggregate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pin {
+    /// Above all other bars.
+    Top,
+    /// Below all other bars.
+    Bottom,
+}
+
+/// Where a [`Progress`] shows its progress beyond the bars themselves. See
+/// [`Progress::with_mirror`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mirror {
+    /// In the tab or taskbar, via the `ESC ] 9 ; 4` sequence understood by
+    /// Windows Terminal, WezTerm, Ghostty, Konsole and others.
+    Taskbar,
+    /// In the window title, like `45% Downloading`. The title is emptied
+    /// afterwards, and the original one restored for terminals that support
+    /// that.
+    Title,
+}
+
+/// The bars tracked by an aggregate bar.
+#[derive(Debug)]
+enum Source {
+    /// Every bar that isn't itself an aggregate, nor has children of its own.
+    All,
+    /// The bars nested directly under this one.
+    Children,
+    /// Only these bars.
+    Group(Vec<usize>),
+}
+
+/// The bars without a line of their own.
+#[derive(Debug, Clone, Copy)]
+struct Folded {
+    /// The line of their summary.
+    row: usize,
+    /// How many there are.
+    more: usize,
+    /// How many of those are done.
+    done: usize,
 }
 
 /// A progress bar index for use with [`Progress`].
@@ -386,15 +1946,16 @@ struct SubBar {
 /// ```
 ///
 /// As shown above, this type can only be constructed via [`Progress::bar`].
+///
+/// A `Bar` belongs to the `Progress` that made it. Using it with any other is
+/// caught by a debug assertion, or reported as [`Error::ForeignBar`] by the
+/// checked `try_*` methods.
 #[derive(Debug)]
-pub struct Bar(usize);
-
-/// Reduce some raw byte count into a more human-readable form.
-fn denomination(curr: usize) -> (usize, char) {
-    match curr {
-        _ if curr >= 1_000_000_000 => (curr / 1_000_000_000, 'G'),
-        _ if curr >= 1_000_000 => (curr / 1_000_000, 'M'),
-        _ if curr >= 1000 => (curr / 1000, 'K'),
-        _ => (curr, ' '),
+pub struct Bar(usize, usize);
+
+impl Bar {
+    /// Another handle to the same bar.
+    pub(crate) fn dup(&self) -> Bar {
+        Bar(self.0, self.1)
     }
 }
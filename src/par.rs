@@ -0,0 +1,138 @@
+//! Bars driven by `rayon` parallel iterators.
+
+use crate::{Coordinator, SharedBar};
+use rayon::iter::plumbing::{Consumer, ProducerCallback, UnindexedConsumer};
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use std::sync::Mutex;
+
+/// Drive a bar automatically from a `rayon` parallel iterator. Requires the
+/// `rayon` feature.
+///
+/// ```
+/// use linya::{ParallelProgressIterator, Progress};
+/// use rayon::prelude::*;
+///
+/// let mut progress = Progress::new();
+///
+/// (0..1000).into_par_iter().progress_with(&mut progress, "Crunching").for_each(|n| {
+///     // ... Your logic ...
+/// });
+/// ```
+///
+/// The bar is advanced atomically from the worker threads, and drawn by
+/// whichever of them finds the `Progress` free at the time, so the workers
+/// never wait on each other.
+pub trait ParallelProgressIterator: IndexedParallelIterator {
+    /// Create a bar sized to this iterator's length, and advance it with
+    /// every item.
+    ///
+    /// Any [`Coordinator`] that can be sent between threads will do, such as a
+    /// `&mut Progress` or a `&Mutex<Progress>`.
+    fn progress_with<C, S>(self, mut coordinator: C, label: S) -> ParProgressed<Self, C>
+    where
+        C: Coordinator + Send,
+        S: Into<String>,
+    {
+        let len = self.len();
+        let shared = coordinator.with(|p| {
+            let bar = p.bar(len, label);
+            p.share(&bar)
+        });
+
+        ParProgressed {
+            iter: self,
+            coordinator,
+            shared,
+        }
+    }
+}
+
+impl<I: IndexedParallelIterator> ParallelProgressIterator for I {}
+
+/// A parallel iterator that advances a bar with every item. See
+/// [`ParallelProgressIterator`].
+#[derive(Debug)]
+pub struct ParProgressed<I, C> {
+    iter: I,
+    coordinator: C,
+    shared: SharedBar,
+}
+
+impl<I, C> ParProgressed<I, C>
+where
+    C: Coordinator + Send,
+{
+    /// Run the wrapped iterator, advancing the bar with every item, and draw
+    /// the bar a final time once it's done.
+    fn run<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(I, &(dyn Fn() + Sync)) -> R,
+    {
+        let ParProgressed {
+            iter,
+            coordinator,
+            shared,
+        } = self;
+        let coordinator = Mutex::new(coordinator);
+
+        let tick = || {
+            shared.inc(1);
+
+            // Whoever finds the lock free draws on behalf of everyone else.
+            if let Ok(mut c) = coordinator.try_lock() {
+                c.with(|p| p.draw(shared.as_bar()));
+            }
+        };
+        let result = f(iter, &tick);
+
+        coordinator
+            .into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+            .with(|p| p.draw(shared.as_bar()));
+
+        result
+    }
+}
+
+impl<I, C> ParallelIterator for ParProgressed<I, C>
+where
+    I: IndexedParallelIterator,
+    C: Coordinator + Send,
+{
+    type Item = I::Item;
+
+    fn drive_unindexed<Q>(self, consumer: Q) -> Q::Result
+    where
+        Q: UnindexedConsumer<Self::Item>,
+    {
+        self.run(|iter, tick| iter.inspect(|_| tick()).drive_unindexed(consumer))
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        self.iter.opt_len()
+    }
+}
+
+impl<I, C> IndexedParallelIterator for ParProgressed<I, C>
+where
+    I: IndexedParallelIterator,
+    C: Coordinator + Send,
+{
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+
+    fn drive<Q>(self, consumer: Q) -> Q::Result
+    where
+        Q: Consumer<Self::Item>,
+    {
+        self.run(|iter, tick| iter.inspect(|_| tick()).drive(consumer))
+    }
+
+    fn with_producer<CB>(self, callback: CB) -> CB::Output
+    where
+        CB: ProducerCallback<Self::Item>,
+    {
+        self.run(|iter, tick| iter.inspect(|_| tick()).with_producer(callback))
+    }
+}
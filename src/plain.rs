@@ -0,0 +1,59 @@
+//! Progress as plain lines of text, for logs and CI.
+
+use std::time::Duration;
+
+/// How often bars report their progress as plain lines of text, when they
+/// can't be drawn in place on a terminal.
+///
+/// Instead of being redrawn, each bar then appends a line like
+/// `Downloading: 45% (450/1000)` whenever its progress crosses another `step`
+/// percent, or has changed at all after `interval` has passed, and once more
+/// when it finishes. Bars of unknown total only report the latter way.
+///
+/// ```
+/// use linya::{Plain, Progress};
+/// use std::time::Duration;
+///
+/// let plain = Plain::new()
+///     .with_step(25)
+///     .with_interval(Duration::from_secs(30));
+///
+/// let mut progress = Progress::new().with_plain(plain);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Plain {
+    /// Report every time progress crosses another multiple of this percent.
+    pub(crate) step: usize,
+    /// Report any progress at all after this long.
+    pub(crate) interval: Duration,
+}
+
+impl Default for Plain {
+    fn default() -> Plain {
+        Plain {
+            step: 10,
+            interval: Duration::from_secs(5),
+        }
+    }
+}
+
+impl Plain {
+    /// Report every 10%, or every 5 seconds. The default.
+    pub fn new() -> Plain {
+        Plain::default()
+    }
+
+    /// Report every time progress crosses another multiple of this percent.
+    /// A `step` of `0` is taken as `1`.
+    pub fn with_step(mut self, step: usize) -> Plain {
+        self.step = step.max(1);
+        self
+    }
+
+    /// Report any progress that was made after this long, even if it didn't
+    /// amount to another step.
+    pub fn with_interval(mut self, interval: Duration) -> Plain {
+        self.interval = interval;
+        self
+    }
+}
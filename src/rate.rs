@@ -0,0 +1,83 @@
+//! Time-based measurements of bar progress.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How many samples to keep in the moving window.
+const SAMPLES: usize = 20;
+
+/// The minimum time between two samples. Together with [`SAMPLES`] this means
+/// the window covers at least the last five seconds of progress.
+const INTERVAL: Duration = Duration::from_millis(250);
+
+/// A moving window of `(time, progress)` samples, from which a smoothed
+/// throughput can be calculated.
+///
+/// Smoothing over a window keeps the reported rate (and thus the ETA) from
+/// jumping around when progress arrives in bursts.
+#[derive(Debug, Clone)]
+pub(crate) struct Rate {
+    /// A ring buffer of samples.
+    samples: [(Instant, usize); SAMPLES],
+    /// The number of samples recorded so far, up to [`SAMPLES`].
+    len: usize,
+    /// The index of the most recent sample.
+    newest: usize,
+}
+
+impl Rate {
+    /// Start a new window, with no progress made as of `start`.
+    pub(crate) fn new(start: Instant) -> Rate {
+        Rate {
+            samples: [(start, 0); SAMPLES],
+            len: 1,
+            newest: 0,
+        }
+    }
+
+    /// Record the progress value as of `now`. Samples that arrive faster than
+    /// [`INTERVAL`] are ignored.
+    pub(crate) fn record(&mut self, now: Instant, value: usize) {
+        let (then, _) = self.samples[self.newest];
+
+        if now.saturating_duration_since(then) >= INTERVAL {
+            self.newest = (self.newest + 1) % SAMPLES;
+            self.samples[self.newest] = (now, value);
+            self.len = (self.len + 1).min(SAMPLES);
+        }
+    }
+
+    /// Progress per second over the window, given the current value.
+    pub(crate) fn per_sec(&self, now: Instant, value: usize) -> f64 {
+        let oldest = if self.len < SAMPLES {
+            0
+        } else {
+            (self.newest + 1) % SAMPLES
+        };
+        let (then, start) = self.samples[oldest];
+        let secs = now.saturating_duration_since(then).as_secs_f64();
+
+        if secs > 0.0 {
+            value.saturating_sub(start) as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A [`Duration`] formatted to always be five characters wide: `MM:SS` under an
+/// hour, and `HHhMM` after that.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Clock(pub(crate) Duration);
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0.as_secs();
+
+        match secs {
+            _ if secs < 3600 => write!(f, "{:02}:{:02}", secs / 60, secs % 60),
+            _ if secs < 360_000 => write!(f, "{:02}h{:02}", secs / 3600, (secs / 60) % 60),
+            _ => write!(f, "99h+ "),
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Bars of other processes, reported over a pipe or socket.
+//!
+//! A child process reports its bars with a [`ProgressClient`], one line of
+//! text per change, and the parent maps them onto bars of its own `Progress`
+//! with a [`ProgressServer`]. The lines look like:
+//!
+//! ```text
+//! bar 0 1000 Compiling core
+//! bar 1 - Fetching index
+//! inc 0 10
+//! set 0 500
+//! total 1 2048
+//! cancel 1
+//! msg Some message
+//! ```
+//!
+//! That is, a command, the client's number for the bar, and an argument, with
+//! `-` standing for an unknown total. Labels and messages run to the end of
+//! the line.
+
+use crate::{Bar, Coordinator};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+
+/// The child's end of a remote `Progress`, which writes its bars as lines of
+/// text to a pipe, a socket, or any other `Write`.
+///
+/// ```
+/// use linya::ProgressClient;
+///
+/// let mut client = ProgressClient::new(std::io::stdout());
+/// let bar = client.bar(50, "Downloading");
+/// client.inc(&bar, 10);
+/// ```
+///
+/// Nothing is drawn here, and errors in writing are ignored, as they are when
+/// drawing: a parent that stops listening is no reason for the child to fail.
+#[derive(Debug)]
+pub struct ProgressClient<W: Write> {
+    out: W,
+    /// The number of the next bar.
+    next: usize,
+}
+
+/// A handle to a bar of a [`ProgressClient`].
+#[derive(Debug)]
+pub struct RemoteBar(usize);
+
+impl<W: Write> ProgressClient<W> {
+    /// Report bars to the given target, likely one end of a pipe.
+    pub fn new(out: W) -> ProgressClient<W> {
+        ProgressClient { out, next: 0 }
+    }
+
+    /// Create a new bar in the parent's `Progress`. See
+    /// [`Progress::bar`](crate::Progress::bar).
+    pub fn bar<S: Into<String>>(&mut self, total: usize, label: S) -> RemoteBar {
+        self.create(Some(total), label.into())
+    }
+
+    /// Create a new bar of unknown total in the parent's `Progress`. See
+    /// [`Progress::indeterminate`](crate::Progress::indeterminate).
+    pub fn indeterminate<S: Into<String>>(&mut self, label: S) -> RemoteBar {
+        self.create(None, label.into())
+    }
+
+    /// Set a bar's progress value.
+    pub fn set(&mut self, bar: &RemoteBar, value: usize) {
+        self.send(format_args!("set {} {}", bar.0, value));
+    }
+
+    /// Increment a bar's progress.
+    pub fn inc(&mut self, bar: &RemoteBar, value: usize) {
+        self.send(format_args!("inc {} {}", bar.0, value));
+    }
+
+    /// Set the target total of a bar.
+    pub fn set_total(&mut self, bar: &RemoteBar, total: usize) {
+        self.send(format_args!("total {} {}", bar.0, total));
+    }
+
+    /// Cancel a bar.
+    pub fn cancel(&mut self, bar: RemoteBar) {
+        self.send(format_args!("cancel {}", bar.0));
+    }
+
+    /// Write a message above the parent's bars. See
+    /// [`Progress::stderr`](crate::Progress::stderr).
+    pub fn message(&mut self, text: &str) {
+        self.send(format_args!("msg {}", one_line(text)));
+    }
+
+    /// A reference to the underlying output target.
+    pub fn get_ref(&self) -> &W {
+        &self.out
+    }
+
+    /// Unwrap this `ProgressClient`, returning the output target.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn create(&mut self, total: Option<usize>, label: String) -> RemoteBar {
+        let bar = RemoteBar(self.next);
+        self.next += 1;
+
+        let label = one_line(&label);
+        match total {
+            Some(total) => self.send(format_args!("bar {} {} {}", bar.0, total, label)),
+            None => self.send(format_args!("bar {} - {}", bar.0, label)),
+        }
+
+        bar
+    }
+
+    /// Write a single line, and flush it so that the parent hears of it
+    /// straight away.
+    fn send(&mut self, line: std::fmt::Arguments<'_>) {
+        let _ = writeln!(self.out, "{}", line);
+        let _ = self.out.flush();
+    }
+}
+
+/// The parent's end of a remote `Progress`, which creates and advances bars
+/// as told by a [`ProgressClient`].
+///
+/// ```no_run
+/// use linya::{Progress, ProgressServer};
+/// use std::io::BufReader;
+/// use std::process::{Command, Stdio};
+/// use std::sync::Mutex;
+///
+/// let progress = Mutex::new(Progress::new());
+///
+/// let mut child = Command::new("worker").stdout(Stdio::piped()).spawn().unwrap();
+/// let stdout = BufReader::new(child.stdout.take().unwrap());
+///
+/// ProgressServer::new(&progress).serve(stdout).unwrap();
+/// child.wait().unwrap();
+/// ```
+///
+/// Every client needs its own server, as each numbers its bars on its own.
+/// With many children, each server can be run on a thread of its own, with
+/// the `Progress` shared behind a `Mutex`.
+#[derive(Debug)]
+pub struct ProgressServer<C> {
+    coordinator: C,
+    /// The client's bars, by their number.
+    bars: HashMap<usize, Bar>,
+}
+
+impl<C: Coordinator> ProgressServer<C> {
+    /// Map the bars of a client onto those of the `Progress` reachable through
+    /// `coordinator`.
+    pub fn new(coordinator: C) -> ProgressServer<C> {
+        ProgressServer {
+            coordinator,
+            bars: HashMap::new(),
+        }
+    }
+
+    /// Apply every line read from a client until it hangs up.
+    ///
+    /// Bars that haven't finished by then, or by the time reading fails, are
+    /// cancelled, as the client is unlikely to come back to them. Lines that
+    /// aren't part of the protocol, or aren't even text, are ignored, so a
+    /// client is free to print other things to the same pipe.
+    pub fn serve<R: BufRead>(&mut self, mut reader: R) -> io::Result<()> {
+        let result = self.listen(&mut reader);
+
+        let mut left: Vec<_> = self.bars.drain().collect();
+        left.sort_unstable_by_key(|(id, _)| *id);
+
+        for (_, bar) in left {
+            self.coordinator.with(|p| {
+                if !p.is_done(&bar) {
+                    p.cancel(bar);
+                }
+            });
+        }
+
+        result
+    }
+
+    /// Apply lines until the client hangs up, or reading fails.
+    fn listen<R: BufRead>(&mut self, reader: &mut R) -> io::Result<()> {
+        let mut bytes = Vec::new();
+
+        loop {
+            bytes.clear();
+            if reader.read_until(b'\n', &mut bytes)? == 0 {
+                return Ok(());
+            }
+
+            let line = String::from_utf8_lossy(&bytes);
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            self.apply(line);
+        }
+    }
+
+    /// Apply a single line from a client. Returns `false` if the line wasn't
+    /// understood, refers to a bar the client never made, or makes one again
+    /// that it already has.
+    pub fn apply(&mut self, line: &str) -> bool {
+        let mut words = line.splitn(2, ' ');
+        let command = words.next().unwrap_or("");
+        let rest = words.next().unwrap_or("");
+
+        match command {
+            "msg" => self.coordinator.with(|p| {
+                let _ = writeln!(p.stderr(), "{}", rest);
+                true
+            }),
+            "bar" => {
+                let mut args = rest.splitn(3, ' ');
+                let id = args.next().and_then(|n| n.parse().ok());
+                let total = args.next().map(|t| match t {
+                    "-" => Ok(None),
+                    t => t.parse().map(Some),
+                });
+                let label = args.next().unwrap_or("");
+
+                match (id, total) {
+                    (Some(id), Some(Ok(total))) if !self.bars.contains_key(&id) => {
+                        let bar = self.coordinator.with(|p| match total {
+                            Some(total) => p.bar(total, label),
+                            None => p.indeterminate(label),
+                        });
+                        self.bars.insert(id, bar);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            "cancel" => match rest.parse().ok().and_then(|id| self.bars.remove(&id)) {
+                Some(bar) => {
+                    self.coordinator.with(|p| p.cancel(bar));
+                    true
+                }
+                None => false,
+            },
+            "inc" | "set" | "total" => {
+                let mut args = rest.splitn(2, ' ');
+                let bar = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .and_then(|id| self.bars.get(&id));
+                let value = args.next().and_then(|n| n.parse().ok());
+
+                match (bar, value) {
+                    (Some(bar), Some(value)) => {
+                        self.coordinator.with(|p| match command {
+                            "inc" => p.inc_and_draw(bar, value),
+                            "set" => p.set_and_draw(bar, value),
+                            _ => {
+                                p.set_total(bar, value);
+                                p.draw(bar);
+                            }
+                        });
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The given text, with any line breaks that would upset the protocol turned
+/// into spaces.
+fn one_line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
@@ -0,0 +1,333 @@
+//! Rendering of individual bar lines.
+//!
+//! Lines are rendered into a reusable `String` before being written out, so
+//! that they can be measured and truncated to the width of the terminal.
+//! Measurements skip over escape sequences, since they take up no space.
+
+use crate::color::RESET;
+use crate::rate::Clock;
+use crate::{BarStyle, Colors, SubBar};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// When clocks are shown, bars are redrawn at least this often even if their
+/// progress hasn't advanced, so that the clocks keep moving.
+pub(crate) const RATES_REFRESH: Duration = Duration::from_secs(1);
+
+/// The time between animation frames of indeterminate bars and spinners.
+pub(crate) const SPIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Animation frames of the spinner shown by indeterminate bars.
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// How a bar should be drawn, as decided by its own settings and those of its
+/// `Progress`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Look<'a> {
+    /// The characters to draw with.
+    pub(crate) style: &'a BarStyle,
+    /// The colours to draw with, or `None` if colour is disabled.
+    pub(crate) colors: Option<&'a Colors>,
+}
+
+/// The default layout of a bar line: the label, the current count, the bar
+/// itself and the percentage, optionally followed by the elapsed time,
+/// throughput and ETA.
+pub(crate) fn line(
+    s: &mut String,
+    b: &SubBar,
+    look: Look,
+    l: usize,
+    w: usize,
+    rates: bool,
+    now: Instant,
+) {
+    let (data, unit) = denomination(b.curr);
+    label(s, b, l);
+    let _ = write!(s, " {:3}{} [", data, unit);
+    bar(s, b, look, w, now);
+    s.push_str("] ");
+    percent(s, b, now);
+
+    if rates {
+        let (data, unit) = denomination(per_sec(b, now) as usize);
+        let _ = write!(s, " {} {:3}{}/s ETA ", Clock(elapsed(b, now)), data, unit);
+        eta(s, b, now);
+    }
+}
+
+/// The default layout of a bar that has just been created.
+pub(crate) fn initial(s: &mut String, b: &SubBar, look: Look, l: usize, w: usize, rates: bool) {
+    label(s, b, l);
+    s.push_str("      [");
+    fill(s, look.style.empty, w);
+    s.push(']');
+
+    if b.total.is_some() {
+        s.push_str("   0%");
+    } else {
+        s.push_str("     ");
+    }
+
+    if rates {
+        let _ = write!(s, " {}   0 /s ETA --:--", Clock(Duration::ZERO));
+    }
+}
+
+/// The label of a bar followed by its message, if any, padded to `l`
+/// characters. The message is cut short to fit, but the label never is.
+fn label(s: &mut String, b: &SubBar, l: usize) {
+    let start = s.len();
+    s.push_str(&b.label);
+
+    // A message is left out entirely if not even a character of it would fit.
+    let room = l.saturating_sub(width(s, start) + 1);
+    let fits = width(&b.message, 0) <= room;
+    if !b.message.is_empty() && (fits || room > 1) {
+        s.push(' ');
+        let at = s.len();
+        s.push_str(&b.message);
+
+        if !fits {
+            truncate(s, at, room - 1);
+            s.push('…');
+        }
+    }
+
+    let pad = l.saturating_sub(width(s, start));
+    fill(s, ' ', pad);
+}
+
+/// The line of a bar that has finished, in place of the bar itself:
+/// `✓ Downloading 3 — 12M in 4.2s`.
+pub(crate) fn summary(s: &mut String, b: &SubBar, look: Look, now: Instant) {
+    let (mark, paint) = if b.cancelled {
+        ('✗', look.colors.map(|c| c.cancelled))
+    } else {
+        ('✓', look.colors.map(|c| c.done))
+    };
+
+    match paint.filter(|p| !p.is_plain()) {
+        Some(paint) => {
+            let _ = write!(s, "{}{}{}", paint, mark, RESET);
+        }
+        None => s.push(mark),
+    }
+
+    let _ = write!(s, " {} — ", b.label);
+    scaled(s, b.curr);
+
+    let secs = elapsed(b, now).as_secs_f64();
+    if b.cancelled {
+        let _ = write!(s, ", cancelled after {:.1}s", secs);
+    } else {
+        let _ = write!(s, " in {:.1}s", secs);
+    }
+}
+
+/// The line of a bar reported as plain text, rather than drawn in place:
+/// `Downloading: 45% (450/1000)`.
+pub(crate) fn plain(s: &mut String, b: &SubBar) {
+    let _ = write!(s, "{}: ", b.label);
+
+    match b.total {
+        _ if b.cancelled => s.push_str("cancelled"),
+        Some(total) => {
+            let percent = (100 * b.curr as u64)
+                .checked_div(total as u64)
+                .unwrap_or(100);
+            let _ = write!(s, "{}% ({}/{})", percent, b.curr, total);
+        }
+        None => {
+            let _ = write!(s, "{}", b.curr);
+        }
+    }
+}
+
+/// The line standing in for all bars that don't fit on screen:
+/// `+37 more (12 done)`.
+pub(crate) fn folded(s: &mut String, more: usize, done: usize) {
+    let _ = write!(s, "+{} more ({} done)", more, done);
+}
+
+/// The inside of a bar of width `w`, coloured according to its state.
+pub(crate) fn bar(s: &mut String, b: &SubBar, look: Look, w: usize, now: Instant) {
+    let style = look.style;
+    let paint = look
+        .colors
+        .map(|c| match b.total {
+            _ if b.cancelled => c.cancelled,
+            Some(total) if b.curr >= total => c.done,
+            _ => c.progress,
+        })
+        .filter(|p| !p.is_plain());
+
+    if let Some(paint) = paint {
+        let _ = write!(s, "{}", paint);
+    }
+
+    match b.total {
+        _ if b.cancelled => fill(s, style.cancelled, w),
+        Some(total) if b.curr >= total => fill(s, style.fill, w),
+        Some(total) => {
+            // With more than one partial character, each cell of the bar is
+            // split into that many steps.
+            let steps = style.partials.len() as u64;
+            let units = (w as u64) * steps * (b.curr as u64) / (total as u64);
+            let f = ((units / steps) as usize).min(w - 1);
+
+            fill(s, style.fill, f);
+            s.push(style.partials[(units % steps) as usize]);
+            fill(s, style.empty, (w - 1) - f);
+        }
+        None => bounce(s, style, w, frame(b, now)),
+    }
+
+    if paint.is_some() {
+        s.push_str(RESET);
+    }
+}
+
+/// An indeterminate bar of width `w` as of the given animation frame: a block
+/// bouncing back and forth.
+fn bounce(s: &mut String, style: &BarStyle, w: usize, frame: usize) {
+    let block = style.block.chars().count();
+    let track = w.saturating_sub(block);
+    let pos = match frame % (2 * track).max(1) {
+        p if p > track => 2 * track - p,
+        p => p,
+    };
+
+    if w >= block {
+        fill(s, style.empty, pos);
+        s.push_str(&style.block);
+        fill(s, style.empty, track - pos);
+    } else {
+        fill(s, style.empty, w);
+    }
+}
+
+/// The percentage of completion, always four characters wide. Indeterminate
+/// bars show a spinner instead.
+pub(crate) fn percent(s: &mut String, b: &SubBar, now: Instant) {
+    match b.total {
+        _ if b.cancelled => s.push_str("??? "),
+        Some(total) if b.curr >= total => s.push_str("100%"),
+        Some(total) => {
+            let _ = write!(s, "{:3}%", (100 * b.curr as u64) / (total as u64));
+        }
+        None => {
+            let _ = write!(s, "  {} ", spinner(b, now));
+        }
+    }
+}
+
+/// The current frame of a spinner.
+pub(crate) fn spinner(b: &SubBar, now: Instant) -> char {
+    SPINNER[frame(b, now) % SPINNER.len()]
+}
+
+/// The animation frame of a bar, counting from its creation.
+fn frame(b: &SubBar, now: Instant) -> usize {
+    (now.saturating_duration_since(b.started).as_millis() / SPIN_INTERVAL.as_millis()) as usize
+}
+
+/// How long the bar has been running, or ran for if it has finished.
+pub(crate) fn elapsed(b: &SubBar, now: Instant) -> Duration {
+    b.ended.unwrap_or(now).saturating_duration_since(b.started)
+}
+
+/// The bar's throughput per second.
+pub(crate) fn per_sec(b: &SubBar, now: Instant) -> f64 {
+    let secs = elapsed(b, now).as_secs_f64();
+
+    match b.ended {
+        // Once finished, report the average over the bar's whole lifetime.
+        Some(_) if secs > 0.0 => b.curr as f64 / secs,
+        Some(_) => 0.0,
+        None => b.rate.per_sec(now, b.curr),
+    }
+}
+
+/// The estimated time remaining, or `--:--` if that can't be known.
+pub(crate) fn eta(s: &mut String, b: &SubBar, now: Instant) {
+    let per_sec = per_sec(b, now);
+
+    match b.total {
+        _ if b.cancelled => s.push_str("--:--"),
+        Some(total) if b.curr >= total => {
+            let _ = write!(s, "{}", Clock(Duration::ZERO));
+        }
+        Some(total) if per_sec > 0.0 => {
+            let eta = (total - b.curr) as f64 / per_sec;
+            let _ = write!(s, "{}", Clock(Duration::from_secs_f64(eta.min(1e9))));
+        }
+        _ => s.push_str("--:--"),
+    }
+}
+
+/// Append `n` copies of a character.
+pub(crate) fn fill(s: &mut String, c: char, n: usize) {
+    s.extend(std::iter::repeat_n(c, n));
+}
+
+/// Cut off everything in `s` past `width` characters, counting from `start`.
+pub(crate) fn truncate(s: &mut String, start: usize, width: usize) {
+    let cut = visible(&s[start..]).nth(width).map(|(i, _)| i);
+
+    if let Some(i) = cut {
+        // Colours may have been cut off before they were reset.
+        let escaped = s[start..start + i].contains('\x1B');
+        s.truncate(start + i);
+
+        if escaped {
+            s.push_str(RESET);
+        }
+    }
+}
+
+/// The width of `s` in characters, counting from `start`.
+pub(crate) fn width(s: &str, start: usize) -> usize {
+    visible(&s[start..]).count()
+}
+
+/// The characters of `s` that take up space on screen, and their indices.
+/// That is, everything but ANSI escape sequences.
+fn visible(s: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut escape = false;
+    let mut chars = s.char_indices().peekable();
+
+    std::iter::from_fn(move || loop {
+        let (i, c) = chars.next()?;
+
+        match c {
+            '\x1B' if chars.peek().map(|(_, c)| *c == '[').unwrap_or(false) => {
+                chars.next();
+                escape = true;
+            }
+            '\x40'..='\x7E' if escape => escape = false,
+            _ if escape => {}
+            _ => return Some((i, c)),
+        }
+    })
+}
+
+/// A value scaled to `K`, `M` or `G`, without padding.
+pub(crate) fn scaled(s: &mut String, value: usize) {
+    let (data, unit) = denomination(value);
+    let _ = write!(s, "{}", data);
+
+    if unit != ' ' {
+        s.push(unit);
+    }
+}
+
+/// Reduce some raw byte count into a more human-readable form.
+pub(crate) fn denomination(curr: usize) -> (usize, char) {
+    match curr {
+        _ if curr >= 1_000_000_000 => (curr / 1_000_000_000, 'G'),
+        _ if curr >= 1_000_000 => (curr / 1_000_000, 'M'),
+        _ if curr >= 1000 => (curr / 1000, 'K'),
+        _ => (curr, ' '),
+    }
+}
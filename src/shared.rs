@@ -0,0 +1,78 @@
+//! Bar handles that can be updated without locking.
+
+use crate::Bar;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A thread-safe handle to a [`Bar`], whose progress is updated atomically
+/// without access to its [`Progress`](crate::Progress).
+///
+/// Obtained via [`Progress::share`](crate::Progress::share). Updating a
+/// `SharedBar` only changes its counter; drawing is left to the coordinator,
+/// usually a [`Ticker`](crate::Ticker) which coalesces the updates of all bars
+/// into one redraw per frame. This avoids fighting over a `Mutex` in hot loops:
+///
+/// ```
+/// use linya::{Progress, Ticker};
+/// use rayon::prelude::*;
+/// use std::sync::{Arc, Mutex};
+///
+/// let progress = Arc::new(Mutex::new(Progress::new()));
+/// let ticker = Ticker::spawn(progress.clone(), 15);
+///
+/// let bar = progress.lock().unwrap().bar(1000, "Crunching");
+/// let shared = progress.lock().unwrap().share(&bar);
+///
+/// (0..1000).into_par_iter().for_each(|_| {
+///     // ... Your logic ...
+///
+///     shared.inc(1);
+/// });
+///
+/// drop(ticker);
+/// ```
+///
+/// Cloning a `SharedBar` gives another handle to the same bar.
+#[derive(Debug)]
+pub struct SharedBar {
+    /// The bar's handle within its `Progress`.
+    bar: Bar,
+    /// The progress counter, shared with the `Progress`.
+    counter: Arc<AtomicUsize>,
+}
+
+impl SharedBar {
+    pub(crate) fn new(bar: Bar, counter: Arc<AtomicUsize>) -> SharedBar {
+        SharedBar { bar, counter }
+    }
+
+    /// Increment the bar's progress.
+    pub fn inc(&self, value: usize) {
+        self.counter.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Set the bar's progress.
+    pub fn set(&self, value: usize) {
+        self.counter.store(value, Ordering::Relaxed);
+    }
+
+    /// The bar's current progress.
+    pub fn get(&self) -> usize {
+        self.counter.load(Ordering::Relaxed)
+    }
+
+    /// The underlying [`Bar`], for use with methods of
+    /// [`Progress`](crate::Progress) like [`draw`](crate::Progress::draw).
+    pub fn as_bar(&self) -> &Bar {
+        &self.bar
+    }
+}
+
+impl Clone for SharedBar {
+    fn clone(&self) -> SharedBar {
+        SharedBar {
+            bar: self.bar.dup(),
+            counter: self.counter.clone(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Bars driven by asynchronous streams.
+
+use crate::{Coordinator, Progressed, SharedBar, Tracker};
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Drive a bar automatically by polling a [`Stream`]. Requires the `futures`
+/// feature.
+///
+/// ```
+/// use futures::{executor, stream, StreamExt};
+/// use linya::{Progress, ProgressStream};
+/// use std::sync::{Arc, Mutex};
+///
+/// let progress = Arc::new(Mutex::new(Progress::new()));
+///
+/// executor::block_on(async {
+///     let stream = stream::iter(0..100).progress_with(progress.clone(), "Streaming");
+///     let sum: u32 = stream.fold(0, |acc, n| async move { acc + n }).await;
+/// });
+/// ```
+///
+/// See [the crate docs](crate#async) on sharing a `Progress` between tasks.
+pub trait ProgressStream: Stream + Sized {
+    /// Create a bar for this stream and advance it with every item.
+    ///
+    /// The bar's total comes from the stream's
+    /// [`size_hint`](Stream::size_hint) if that is exact, and otherwise the
+    /// bar is [indeterminate](crate::Progress::indeterminate) until the stream
+    /// ends. If the stream is dropped before that, the bar is cancelled.
+    fn progress_with<C, S>(self, coordinator: C, label: S) -> Progressed<Self, C>
+    where
+        C: Coordinator,
+        S: Into<String>,
+    {
+        let hint = self.size_hint();
+        Progressed::start(self, coordinator, label.into(), hint)
+    }
+
+    /// Advance an existing bar with every item, through a [`SharedBar`]
+    /// rather than its `Progress`, which is then never locked from within a
+    /// task. Drawing is left to a [`Ticker`](crate::Ticker).
+    ///
+    /// ```
+    /// use futures::{executor, stream, StreamExt};
+    /// use linya::{Progress, ProgressStream, Ticker};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let progress = Arc::new(Mutex::new(Progress::new()));
+    /// let ticker = Ticker::spawn(progress.clone(), 15);
+    ///
+    /// let bar = progress.lock().unwrap().bar(100, "Streaming");
+    /// let shared = progress.lock().unwrap().share(&bar);
+    ///
+    /// executor::block_on(async {
+    ///     let stream = stream::iter(0..100).progress_shared(shared);
+    ///     let sum: u32 = stream.fold(0, |acc, n| async move { acc + n }).await;
+    /// });
+    ///
+    /// drop(ticker);
+    /// ```
+    ///
+    /// As there's no `Progress` at hand, the bar keeps the total it was made
+    /// with, and isn't cancelled if the stream is dropped before it ends.
+    fn progress_shared(self, bar: SharedBar) -> Progressed<Self, SharedBar> {
+        Progressed::shared(self, bar)
+    }
+}
+
+impl<S: Stream> ProgressStream for S {}
+
+impl<S: Stream, C: Tracker> Stream for Progressed<S, C> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        // SAFETY: Only the inner stream is pinned along with its `Progressed`,
+        // and it's never moved out of it, not even when dropped. Everything
+        // else is free to move.
+        let this = unsafe { self.get_unchecked_mut() };
+        let item = unsafe { Pin::new_unchecked(this.inner_mut()) }.poll_next(cx);
+
+        if let Poll::Ready(ref item) = item {
+            this.step(item.is_some());
+        }
+
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner().size_hint()
+    }
+}
@@ -0,0 +1,135 @@
+//! The characters that bars are drawn with.
+
+use std::borrow::Cow;
+
+/// The characters that a bar is drawn with.
+///
+/// Set one for all bars with [`Progress::with_style`], or for a single bar with
+/// [`Progress::set_style`]. Several presets are available:
+///
+/// | Preset                 | Looks like                 |
+/// | ---------------------- | -------------------------- |
+/// | [`BarStyle::ascii`]    | `[######>-------]`         |
+/// | [`BarStyle::blocks`]   | `[██████▍       ]`         |
+/// | [`BarStyle::dots`]     | `[⣿⣿⣿⣿⣿⣿⣦⣀⣀⣀⣀⣀⣀⣀]`         |
+///
+/// Custom styles start from [`BarStyle::new`]:
+///
+/// ```
+/// use linya::{BarStyle, Progress};
+///
+/// let style = BarStyle::new('=', '>', ' ').with_cancelled('x');
+/// let mut progress = Progress::new().with_style(style);
+/// ```
+///
+/// [`Progress::with_style`]: crate::Progress::with_style
+/// [`Progress::set_style`]: crate::Progress::set_style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BarStyle {
+    /// The completed part of the bar.
+    pub(crate) fill: char,
+    /// The leading edge of the bar, from least to most filled.
+    pub(crate) partials: Cow<'static, [char]>,
+    /// The remaining part of the bar.
+    pub(crate) empty: char,
+    /// Every character of a cancelled bar.
+    pub(crate) cancelled: char,
+    /// The block that bounces back and forth within indeterminate bars.
+    pub(crate) block: Cow<'static, str>,
+}
+
+impl BarStyle {
+    /// A custom style with a single `head` character at the leading edge of
+    /// the bar.
+    ///
+    /// The cancellation character is `_` and indeterminate bars show three
+    /// `fill` characters bouncing back and forth. See the `with_*` methods to
+    /// change these.
+    pub fn new(fill: char, head: char, empty: char) -> BarStyle {
+        BarStyle {
+            fill,
+            partials: Cow::Owned(vec![head]),
+            empty,
+            cancelled: '_',
+            block: Cow::Owned([fill; 3].iter().collect()),
+        }
+    }
+
+    /// The classic `linya` look of `[####>---]`. This is the default.
+    pub fn ascii() -> BarStyle {
+        BarStyle {
+            fill: '#',
+            partials: Cow::Borrowed(&['>']),
+            empty: '-',
+            cancelled: '_',
+            block: Cow::Borrowed("<=>"),
+        }
+    }
+
+    /// Unicode block characters, drawn with a precision of eighths of a
+    /// character.
+    pub fn blocks() -> BarStyle {
+        BarStyle {
+            fill: '█',
+            partials: Cow::Borrowed(&[' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉']),
+            empty: ' ',
+            cancelled: '░',
+            block: Cow::Borrowed("███"),
+        }
+    }
+
+    /// Unicode braille dots, drawn with a precision of sixths of a character.
+    pub fn dots() -> BarStyle {
+        BarStyle {
+            fill: '⣿',
+            partials: Cow::Borrowed(&['⣀', '⣄', '⣤', '⣦', '⣶', '⣷']),
+            empty: '⣀',
+            cancelled: '⠶',
+            block: Cow::Borrowed("⣿⣿⣿"),
+        }
+    }
+
+    /// Use a sequence of characters at the leading edge of the bar, from least
+    /// to most filled, for sub-character precision.
+    ///
+    /// ```
+    /// use linya::BarStyle;
+    ///
+    /// // Quarters of a character.
+    /// let style = BarStyle::new('#', '>', ' ').with_partials(vec![' ', '.', ':', '|']);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `partials` is empty.
+    pub fn with_partials<P>(mut self, partials: P) -> BarStyle
+    where
+        P: Into<Cow<'static, [char]>>,
+    {
+        let partials = partials.into();
+        assert!(!partials.is_empty(), "Partials must not be empty");
+        self.partials = partials;
+        self
+    }
+
+    /// Fill cancelled bars with this character.
+    pub fn with_cancelled(mut self, cancelled: char) -> BarStyle {
+        self.cancelled = cancelled;
+        self
+    }
+
+    /// Bounce this block back and forth within indeterminate bars.
+    pub fn with_block<B>(mut self, block: B) -> BarStyle
+    where
+        B: Into<Cow<'static, str>>,
+    {
+        self.block = block.into();
+        self
+    }
+}
+
+impl Default for BarStyle {
+    fn default() -> BarStyle {
+        BarStyle::ascii()
+    }
+}
@@ -0,0 +1,299 @@
+//! Format strings for the layout of bar lines.
+
+use crate::rate::Clock;
+use crate::render::{self, Look, RATES_REFRESH, SPIN_INTERVAL};
+use crate::SubBar;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// A custom layout for bar lines, parsed from a format string.
+///
+/// Values are given by keys in braces, like `{label}` or `{percent}`, while
+/// all other text appears as-is:
+///
+/// ```
+/// use linya::{Progress, Template};
+///
+/// let template = Template::new("{label} [{bar}] {pos}/{len} ({eta})").unwrap();
+/// let mut progress = Progress::new().with_template(template);
+/// let bar = progress.bar(100, "Downloading");
+/// progress.inc_and_draw(&bar, 10);
+/// ```
+///
+/// The available keys are:
+///
+/// | Key         | Shows                                                   |
+/// | ----------- | ------------------------------------------------------- |
+/// | `{label}`   | The bar's label.                                        |
+/// | `{msg}`     | The bar's message, if any. See `Progress::set_message`. |
+/// | `{bar}`     | The bar itself, which fills all remaining space.        |
+/// | `{pos}`     | The current progress value.                             |
+/// | `{len}`     | The bar's total, or `?` if it is indeterminate.         |
+/// | `{count}`   | The current progress scaled to `K`, `M` or `G`.         |
+/// | `{percent}` | The percentage of completion.                           |
+/// | `{elapsed}` | The time since the bar was created.                     |
+/// | `{rate}`    | The throughput per second, scaled like `{count}`.       |
+/// | `{eta}`     | The estimated time remaining.                           |
+/// | `{spinner}` | An animated spinner.                                    |
+///
+/// A key can be given a fixed width as in `{label:20}`, in which case its value
+/// is padded or truncated to fit. Values are left-aligned by default, but can
+/// be right-aligned with `{pos:>6}`. If more than one `{bar}` is present, the
+/// remaining space is split between them. Literal braces are written `{{` and
+/// `}}`.
+///
+/// Lines are cut off at the edge of the terminal, should they be too long.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+/// A piece of a [`Template`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    /// Literal text.
+    Text(String),
+    /// A value to fill in.
+    Key(Key, Option<Width>),
+}
+
+/// A fixed width given to a [`Key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Width {
+    /// The number of characters.
+    chars: usize,
+    /// Align to the right, instead of the left?
+    right: bool,
+}
+
+/// The values available to a [`Template`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Label,
+    Message,
+    Bar,
+    Pos,
+    Len,
+    Count,
+    Percent,
+    Elapsed,
+    Rate,
+    Eta,
+    Spinner,
+}
+
+impl FromStr for Key {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Key, TemplateError> {
+        match s {
+            "label" => Ok(Key::Label),
+            "msg" => Ok(Key::Message),
+            "bar" => Ok(Key::Bar),
+            "pos" => Ok(Key::Pos),
+            "len" => Ok(Key::Len),
+            "count" => Ok(Key::Count),
+            "percent" => Ok(Key::Percent),
+            "elapsed" => Ok(Key::Elapsed),
+            "rate" => Ok(Key::Rate),
+            "eta" => Ok(Key::Eta),
+            "spinner" => Ok(Key::Spinner),
+            _ => Err(TemplateError::UnknownKey(s.to_string())),
+        }
+    }
+}
+
+impl Template {
+    /// Parse a format string into a `Template`.
+    pub fn new(format: &str) -> Result<Template, TemplateError> {
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err(TemplateError::Unmatched),
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').ok_or(TemplateError::Unclosed)?;
+                    let (key, width) = match rest[..end].split_once(':') {
+                        Some((key, width)) => (key, Some(parse_width(width)?)),
+                        None => (&rest[..end], None),
+                    };
+
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+
+                    parts.push(Part::Key(key.trim().parse()?, width));
+                    chars = rest[end + 1..].chars();
+                }
+                _ => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Template { parts })
+    }
+
+    /// How often a bar drawn with this template must be redrawn to keep its
+    /// animations and clocks current, if at all.
+    pub(crate) fn refresh(&self) -> Option<Duration> {
+        let keys = || {
+            self.parts.iter().filter_map(|p| match p {
+                Part::Key(k, _) => Some(*k),
+                Part::Text(_) => None,
+            })
+        };
+
+        if keys().any(|k| k == Key::Spinner) {
+            Some(SPIN_INTERVAL)
+        } else if keys().any(|k| matches!(k, Key::Elapsed | Key::Rate | Key::Eta)) {
+            Some(RATES_REFRESH)
+        } else {
+            None
+        }
+    }
+
+    /// Render a bar line into `s`, fitting it to `width` characters.
+    pub(crate) fn render(
+        &self,
+        s: &mut String,
+        b: &SubBar,
+        look: Look,
+        width: usize,
+        now: Instant,
+    ) {
+        let start = s.len();
+        let flexible = self
+            .parts
+            .iter()
+            .filter(|p| matches!(p, Part::Key(Key::Bar, None)))
+            .count();
+
+        // A first pass without the flexible bars, to see how much room is
+        // left for them.
+        self.render_parts(s, b, look, 0, now);
+        let rest = width.saturating_sub(render::width(s, start));
+        s.truncate(start);
+
+        let bar_width = match flexible {
+            0 => 0,
+            n => (rest / n).max(1),
+        };
+        self.render_parts(s, b, look, bar_width, now);
+        render::truncate(s, start, width);
+    }
+
+    fn render_parts(&self, s: &mut String, b: &SubBar, look: Look, bar_width: usize, now: Instant) {
+        for part in self.parts.iter() {
+            match part {
+                Part::Text(t) => s.push_str(t),
+                Part::Key(Key::Bar, None) if bar_width == 0 => {}
+                Part::Key(Key::Bar, None) => render::bar(s, b, look, bar_width, now),
+                Part::Key(Key::Bar, Some(w)) => render::bar(s, b, look, w.chars.max(1), now),
+                Part::Key(key, None) => render_key(s, *key, b, now),
+                Part::Key(key, Some(w)) => {
+                    let start = s.len();
+                    render_key(s, *key, b, now);
+                    render::truncate(s, start, w.chars);
+
+                    let pad = w.chars.saturating_sub(render::width(s, start));
+                    if w.right {
+                        s.insert_str(start, &" ".repeat(pad));
+                    } else {
+                        render::fill(s, ' ', pad);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Template, TemplateError> {
+        Template::new(s)
+    }
+}
+
+/// Render the value of a single key, at its natural width.
+fn render_key(s: &mut String, key: Key, b: &SubBar, now: Instant) {
+    match key {
+        Key::Label => s.push_str(&b.label),
+        Key::Message => s.push_str(&b.message),
+        Key::Bar => {}
+        Key::Pos => {
+            let _ = write!(s, "{}", b.curr);
+        }
+        Key::Len => match b.total {
+            Some(total) => {
+                let _ = write!(s, "{}", total);
+            }
+            None => s.push('?'),
+        },
+        Key::Count => render::scaled(s, b.curr),
+        Key::Percent => render::percent(s, b, now),
+        Key::Elapsed => {
+            let _ = write!(s, "{}", Clock(render::elapsed(b, now)));
+        }
+        Key::Rate => {
+            render::scaled(s, render::per_sec(b, now) as usize);
+            s.push_str("/s");
+        }
+        Key::Eta => render::eta(s, b, now),
+        Key::Spinner => s.push(render::spinner(b, now)),
+    }
+}
+
+/// Parse the width given to a key, like the `>6` of `{pos:>6}`.
+fn parse_width(s: &str) -> Result<Width, TemplateError> {
+    let (right, digits) = match s.strip_prefix('>') {
+        Some(digits) => (true, digits),
+        None => (false, s.strip_prefix('<').unwrap_or(s)),
+    };
+
+    digits
+        .parse()
+        .map(|chars| Width { chars, right })
+        .map_err(|_| TemplateError::BadWidth(s.to_string()))
+}
+
+/// Errors that can occur while parsing a [`Template`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// A key that isn't recognized, like `{foo}`.
+    UnknownKey(String),
+    /// A width that isn't a number, like the `x` of `{label:x}`.
+    BadWidth(String),
+    /// An opening `{` without its closing `}`.
+    Unclosed,
+    /// A closing `}` without its opening `{`.
+    Unmatched,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownKey(k) => write!(f, "Unknown template key: {}", k),
+            TemplateError::BadWidth(w) => write!(f, "Bad template width: {}", w),
+            TemplateError::Unclosed => write!(f, "Unclosed {{ in template"),
+            TemplateError::Unmatched => write!(f, "Unmatched }} in template"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
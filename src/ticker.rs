@@ -0,0 +1,90 @@
+//! A background render thread.
+
+use crate::Progress;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A background thread that redraws all bars of a [`Progress`] at a fixed
+/// frame rate.
+///
+/// With a `Ticker` running, worker threads need only update their bars via
+/// [`Progress::inc`] or [`Progress::set`] and never draw. This keeps the time
+/// spent holding the lock short, and lets spinners and clocks move even when
+/// no progress is being made.
+///
+/// ```
+/// use linya::{Progress, Ticker};
+/// use std::sync::{Arc, Mutex};
+///
+/// let progress = Arc::new(Mutex::new(Progress::new()));
+/// let ticker = Ticker::spawn(progress.clone(), 15);
+///
+/// let bar = progress.lock().unwrap().bar(50, "Downloading");
+/// for _ in 0..50 {
+///     // No drawing necessary.
+///     progress.lock().unwrap().inc(&bar, 1);
+/// }
+///
+/// // Stops the thread after drawing one last time.
+/// drop(ticker);
+/// ```
+///
+/// The thread is stopped when the `Ticker` is dropped, after a final redraw.
+#[derive(Debug)]
+pub struct Ticker {
+    /// Signals the thread to stop.
+    stop: Arc<AtomicBool>,
+    /// The render thread itself.
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Ticker {
+    /// Start redrawing the given `Progress` in the background, `fps` times per
+    /// second.
+    pub fn spawn<W>(progress: Arc<Mutex<Progress<W>>>, fps: u32) -> Ticker
+    where
+        W: Write + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop2 = stop.clone();
+        let interval = Duration::from_secs(1) / fps.max(1);
+
+        let handle = thread::spawn(move || loop {
+            // Checked before drawing, so that there is always a final draw
+            // after the stop was requested.
+            let stopping = stop2.load(Ordering::Acquire);
+
+            // A worker that panicked while holding the lock doesn't prevent
+            // the others from being drawn.
+            progress
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .draw_all();
+
+            if stopping {
+                break;
+            }
+
+            thread::park_timeout(interval);
+        });
+
+        Ticker {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
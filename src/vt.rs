@@ -0,0 +1,345 @@
+//! An in-memory terminal for inspecting rendered output.
+
+use std::io::{self, Write};
+
+/// An in-memory "virtual terminal" that interprets the ANSI sequences emitted
+/// by [`Progress`](crate::Progress) into a grid of cells.
+///
+/// This is mostly useful for testing, be it of `linya` itself or of your own
+/// application's output. Pair it with [`Progress::with_writer`] and
+/// [`Progress::with_size`] to render bars into it:
+///
+/// [`Progress::with_writer`]: crate::Progress::with_writer
+/// [`Progress::with_size`]: crate::Progress::with_size
+///
+/// ```
+/// use linya::{Progress, VirtualTerminal};
+///
+/// let mut progress = Progress::with_writer(VirtualTerminal::new(40, 10)).with_size(40, 10);
+/// let bar = progress.bar(10, "Downloading");
+/// progress.set_and_draw(&bar, 5);
+///
+/// let lines = progress.writer().lines();
+/// assert_eq!("Downloading      5  [######>------]  50%", lines[0]);
+/// ```
+///
+/// Only the subset of sequences that `linya` actually uses is understood.
+/// Anything else is silently ignored. Like a real terminal with the usual
+/// settings, a newline also returns the cursor to the start of the line, and
+/// lines that fall off the top of the screen are moved to the scrollback.
+#[derive(Debug, Clone)]
+pub struct VirtualTerminal {
+    /// Screen width in cells.
+    width: usize,
+    /// Screen height in cells.
+    height: usize,
+    /// The visible screen, row by row.
+    cells: Vec<Vec<char>>,
+    /// Lines that have scrolled off the top of the screen.
+    scrollback: Vec<String>,
+    /// The cursor row.
+    row: usize,
+    /// The cursor column. Equal to `width` when a wrap is pending.
+    col: usize,
+    /// Cursor position saved via `ESC [ s`.
+    saved: Option<(usize, usize)>,
+    /// The window title, if one was set.
+    title: Option<String>,
+    /// Titles saved via `ESC [ 22 ; 2 t`, to be restored later.
+    titles: Vec<Option<String>>,
+    /// The state and percent of the progress shown in the tab or taskbar.
+    taskbar: Option<(u8, u8)>,
+    /// Where we are within an escape sequence, if anywhere.
+    state: State,
+    /// Bytes of an incomplete UTF-8 character from a previous write.
+    partial: Vec<u8>,
+}
+
+/// Parser state between calls to `write`.
+#[derive(Debug, Clone)]
+enum State {
+    /// Regular printable text.
+    Ground,
+    /// Just saw an `ESC`.
+    Escape,
+    /// Within a Control Sequence, accumulating its parameters.
+    Csi(String),
+    /// Within an Operating System Command.
+    Osc(String),
+    /// Saw an `ESC` within an OSC, which may be the start of its terminator.
+    OscEscape(String),
+}
+
+impl VirtualTerminal {
+    /// Create a blank terminal of the given size, with the cursor in the top
+    /// left corner.
+    ///
+    /// # Panics
+    ///
+    /// If either dimension is `0`.
+    pub fn new(width: usize, height: usize) -> VirtualTerminal {
+        assert!(
+            width > 0 && height > 0,
+            "Terminal dimensions must be nonzero"
+        );
+
+        VirtualTerminal {
+            width,
+            height,
+            cells: vec![vec![' '; width]; height],
+            scrollback: vec![],
+            row: 0,
+            col: 0,
+            saved: None,
+            title: None,
+            titles: vec![],
+            taskbar: None,
+            state: State::Ground,
+            partial: vec![],
+        }
+    }
+
+    /// The width of the screen.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the screen.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cursor position as a `(row, column)` pair, counting from zero.
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col.min(self.width - 1))
+    }
+
+    /// Every row of the screen, with trailing whitespace removed.
+    pub fn lines(&self) -> Vec<String> {
+        self.cells.iter().map(|row| render_row(row)).collect()
+    }
+
+    /// The screen contents as a single string, one row per line. Trailing
+    /// whitespace and empty rows at the bottom are removed.
+    pub fn contents(&self) -> String {
+        let mut lines = self.lines();
+
+        while lines.last().map(|l| l.is_empty()).unwrap_or(false) {
+            lines.pop();
+        }
+
+        lines.join("\n")
+    }
+
+    /// The window title, as set via `ESC ] 2`, if any.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The progress shown in the tab or taskbar via `ESC ] 9 ; 4`, if any, as
+    /// a pair of its state and percent. The state is `1` for normal progress,
+    /// `2` for an error, `3` for progress of unknown total and `4` if paused.
+    pub fn taskbar(&self) -> Option<(u8, u8)> {
+        self.taskbar
+    }
+
+    /// Lines that have scrolled off the top of the screen, oldest first.
+    pub fn scrollback(&self) -> &[String] {
+        &self.scrollback
+    }
+
+    /// Interpret a single character of output.
+    fn feed(&mut self, c: char) {
+        match std::mem::replace(&mut self.state, State::Ground) {
+            State::Ground => match c {
+                '\x1B' => self.state = State::Escape,
+                '\r' => self.col = 0,
+                '\n' => {
+                    self.col = 0;
+                    self.line_feed();
+                }
+                '\x07' | '\x08' | '\t' => {}
+                _ => self.print(c),
+            },
+            State::Escape => match c {
+                '[' => self.state = State::Csi(String::new()),
+                ']' => self.state = State::Osc(String::new()),
+                '7' => self.save(),
+                '8' => self.restore(),
+                _ => {}
+            },
+            State::Csi(mut params) => match c {
+                '\x40'..='\x7E' => self.csi(&params, c),
+                _ => {
+                    params.push(c);
+                    self.state = State::Csi(params);
+                }
+            },
+            State::Osc(mut body) => match c {
+                '\x07' => self.osc(&body),
+                '\x1B' => self.state = State::OscEscape(body),
+                _ => {
+                    body.push(c);
+                    self.state = State::Osc(body);
+                }
+            },
+            State::OscEscape(body) => {
+                self.osc(&body);
+
+                if c != '\\' {
+                    self.feed(c);
+                }
+            }
+        }
+    }
+
+    /// Write a printable character at the cursor.
+    fn print(&mut self, c: char) {
+        if self.col >= self.width {
+            self.col = 0;
+            self.line_feed();
+        }
+
+        self.cells[self.row][self.col] = c;
+        self.col += 1;
+    }
+
+    /// Move down a line, scrolling the screen if necessary.
+    fn line_feed(&mut self) {
+        if self.row + 1 < self.height {
+            self.row += 1;
+        } else {
+            let top = self.cells.remove(0);
+            self.scrollback.push(render_row(&top));
+            self.cells.push(vec![' '; self.width]);
+        }
+    }
+
+    fn save(&mut self) {
+        self.saved = Some((self.row, self.col));
+    }
+
+    fn restore(&mut self) {
+        if let Some((row, col)) = self.saved {
+            self.row = row;
+            self.col = col;
+        }
+    }
+
+    /// Perform a Control Sequence.
+    fn csi(&mut self, params: &str, action: char) {
+        let n = params
+            .split(';')
+            .next()
+            .and_then(|p| p.parse::<usize>().ok())
+            .unwrap_or(0);
+
+        match action {
+            // Cursor up and down. A count of 0 is treated as 1.
+            'A' => {
+                self.row = self.row.saturating_sub(n.max(1));
+                self.col = self.col.min(self.width - 1);
+            }
+            'B' => {
+                self.row = (self.row + n.max(1)).min(self.height - 1);
+                self.col = self.col.min(self.width - 1);
+            }
+            // Erase within the current line.
+            'K' => {
+                let cols = match n {
+                    0 => self.col.min(self.width)..self.width,
+                    1 => 0..(self.col + 1).min(self.width),
+                    _ => 0..self.width,
+                };
+                self.cells[self.row][cols].iter_mut().for_each(|c| *c = ' ');
+            }
+            // Erase within the screen.
+            'J' => {
+                let (rows, cols) = match n {
+                    0 => (
+                        self.row + 1..self.height,
+                        self.col.min(self.width)..self.width,
+                    ),
+                    1 => (0..self.row, 0..(self.col + 1).min(self.width)),
+                    _ => (0..self.height, 0..self.width),
+                };
+                self.cells[rows].iter_mut().flatten().for_each(|c| *c = ' ');
+                self.cells[self.row][cols].iter_mut().for_each(|c| *c = ' ');
+            }
+            's' => self.save(),
+            'u' => self.restore(),
+            // Save and restore the window title.
+            't' if params == "22;2" => self.titles.push(self.title.clone()),
+            't' if params == "23;2" => {
+                if let Some(title) = self.titles.pop() {
+                    self.title = title;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Perform an Operating System Command.
+    fn osc(&mut self, body: &str) {
+        match body.split_once(';') {
+            Some(("0" | "2", title)) => self.title = Some(title.to_string()),
+            Some(("9", rest)) => {
+                let mut params = rest.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+
+                if params.next() == Some(4) {
+                    self.taskbar = match (params.next(), params.next()) {
+                        (Some(0) | None, _) => None,
+                        (Some(state), progress) => Some((state, progress.unwrap_or(0))),
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Write for VirtualTerminal {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        let bytes = std::mem::take(&mut self.partial);
+        let mut rest = &bytes[..];
+
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(s) => {
+                    s.chars().for_each(|c| self.feed(c));
+                    break;
+                }
+                Err(e) => {
+                    let (good, bad) = rest.split_at(e.valid_up_to());
+                    let good = std::str::from_utf8(good).unwrap_or_default();
+                    good.chars().for_each(|c| self.feed(c));
+
+                    match e.error_len() {
+                        // Hold back an incomplete character until the next write.
+                        None => {
+                            self.partial = bad.to_vec();
+                            break;
+                        }
+                        Some(len) => {
+                            self.feed(char::REPLACEMENT_CHARACTER);
+                            rest = &bad[len..];
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A row of cells as a `String` without trailing whitespace.
+fn render_row(row: &[char]) -> String {
+    let s: String = row.iter().collect();
+    s.trim_end().to_string()
+}
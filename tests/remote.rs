@@ -0,0 +1,77 @@
+//! Bars reported by a child process, over a pipe.
+
+use linya::{Progress, ProgressClient, ProgressServer, VirtualTerminal};
+use std::io::BufReader;
+use std::process::{Command, Stdio};
+
+/// Set for the copy of this test that plays the child.
+const CHILD: &str = "LINYA_TEST_REMOTE_CHILD";
+
+#[test]
+fn child_processes() {
+    if std::env::var_os(CHILD).is_some() {
+        // The test harness has its own say on stdout.
+        let mut client = ProgressClient::new(std::io::stderr());
+        let a = client.bar(100, "A");
+        let b = client.indeterminate("B\nb");
+        let c = client.bar(10, "C");
+        let _d = client.bar(10, "D");
+
+        client.inc(&a, 50);
+        client.set(&a, 100);
+        client.inc(&b, 20);
+        client.set_total(&b, 40);
+        client.message("Halfway there");
+        client.cancel(c);
+
+        // `D` is left unfinished.
+        return;
+    }
+
+    // Run just this test again, as the child.
+    let exe = std::env::current_exe().unwrap();
+    let mut child = Command::new(exe)
+        .args([
+            "child_processes",
+            "--exact",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env(CHILD, "1")
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stderr = BufReader::new(child.stderr.take().unwrap());
+
+    let mut progress = Progress::with_writer(VirtualTerminal::new(40, 8)).with_size(40, 8);
+    ProgressServer::new(&mut progress).serve(stderr).unwrap();
+    assert!(child.wait().unwrap().success());
+
+    // Unfinished bars are cancelled once the child hangs up.
+    let expected = "\
+Halfway there
+A              100  [#############] 100%
+B b             40  [_____________] ???
+C               10  [_____________] ???
+D               10  [_____________] ???";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn unruly_clients() {
+    let mut progress = Progress::with_writer(VirtualTerminal::new(40, 8)).with_size(40, 8);
+    let mut server = ProgressServer::new(&mut progress);
+
+    // Bytes that aren't text are skipped over like any other noise, and a bar
+    // can't be made twice.
+    let input = b"bar 1 5 Q\n\xff\xfe\nbar 1 5 R\ninc 1 2\n";
+    server.serve(&input[..]).unwrap();
+    assert!(server.apply("bar 0 5 S"));
+    assert!(!server.apply("bar 0 5 T"));
+
+    // `Q` is still cancelled once the client hangs up.
+    let expected = "\
+Q                5  [_____________] ???
+S                   [-------------]   0%";
+    assert_eq!(expected, progress.writer().contents());
+}
@@ -0,0 +1,1051 @@
+//! Snapshot tests of rendered output, via `VirtualTerminal`.
+
+use linya::{
+    BarStyle, Color, ColorChoice, Colors, Error, Mirror, OnFinish, Pin, Plain, Progress,
+    ProgressIterator, ProgressReader, ProgressWriter, Ticker, VirtualTerminal,
+};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::{Arc, Mutex};
+
+fn progress(width: usize, height: usize) -> Progress<VirtualTerminal> {
+    Progress::with_writer(VirtualTerminal::new(width, height)).with_size(width, height)
+}
+
+#[test]
+fn single_bar() {
+    let mut progress = progress(40, 5);
+    let bar = progress.bar(50, "Hacking...");
+    assert_eq!(
+        "Hacking...          [-------------]   0%",
+        progress.writer().contents()
+    );
+
+    progress.set_and_draw(&bar, 25);
+    assert_eq!(
+        "Hacking...      25  [######>------]  50%",
+        progress.writer().contents()
+    );
+    assert_eq!((1, 0), progress.writer().cursor());
+
+    progress.set_and_draw(&bar, 50);
+    assert_eq!(
+        "Hacking...      50  [#############] 100%",
+        progress.writer().contents()
+    );
+    assert!(progress.is_done(&bar));
+}
+
+#[test]
+fn multi_bars() {
+    let mut progress = progress(40, 5);
+    let a = progress.bar(100, "A");
+    let b = progress.bar(2000, "B");
+    let c = progress.bar(100, "C");
+
+    progress.set_and_draw(&b, 1500);
+    progress.set_and_draw(&a, 10);
+    progress.inc_and_draw(&c, 100);
+
+    let expected = "\
+A               10  [#>-----------]  10%
+B                1K [#########>---]  75%
+C              100  [#############] 100%";
+    assert_eq!(expected, progress.writer().contents());
+    assert_eq!((3, 0), progress.writer().cursor());
+}
+
+#[test]
+fn cancelled_bar() {
+    let mut progress = progress(40, 5);
+    let a = progress.bar(10, "A");
+    let b = progress.bar(10, "B");
+
+    progress.set_and_draw(&a, 3);
+    progress.cancel(b);
+
+    let expected = "\
+A                3  [###>---------]  30%
+B               10  [_____________] ???";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn stderr_messages() {
+    let mut progress = progress(40, 5);
+    let a = progress.bar(10, "A");
+    let b = progress.bar(10, "B");
+    progress.set_and_draw(&a, 5);
+
+    writeln!(progress.stderr(), "First").unwrap();
+    writeln!(progress.stderr(), "Second").unwrap();
+    progress.set_and_draw(&b, 10);
+
+    let expected = "\
+First
+Second
+A                5  [######>------]  50%
+B               10  [#############] 100%";
+    assert_eq!(expected, progress.writer().contents());
+    assert_eq!((4, 0), progress.writer().cursor());
+}
+
+#[test]
+fn stderr_messages_scroll() {
+    let mut progress = progress(40, 3);
+    let _ = progress.bar(10, "A");
+
+    for n in 0..4 {
+        writeln!(progress.stderr(), "Message {}", n).unwrap();
+    }
+
+    let expected = "\
+Message 3
+A                0  [>------------]   0%";
+    assert_eq!(expected, progress.writer().contents());
+    assert_eq!(
+        &["Message 0", "Message 1", "Message 2"],
+        progress.writer().scrollback()
+    );
+}
+
+#[test]
+fn terminal_escapes() {
+    let mut term = VirtualTerminal::new(10, 3);
+    write!(term, "abc\ndef\x1B[s\x1B[1A\rX\x1B[u!").unwrap();
+    assert_eq!("Xbc\ndef!", term.contents());
+
+    write!(term, "\r\x1B[2K\x1B[1AYZ").unwrap();
+    assert_eq!("YZc", term.contents());
+}
+
+#[test]
+fn terminal_wrapping_and_unicode() {
+    let mut term = VirtualTerminal::new(4, 2);
+
+    // A multi-byte character split across two writes.
+    let bytes = "ab█cdefg".as_bytes();
+    term.write_all(&bytes[..3]).unwrap();
+    term.write_all(&bytes[3..]).unwrap();
+
+    assert_eq!("ab█c\ndefg", term.contents());
+    assert!(term.scrollback().is_empty());
+
+    // Wrapping only occurs once there is something more to print.
+    write!(term, "h").unwrap();
+    assert_eq!("defg\nh", term.contents());
+    assert_eq!(&["ab█c"], term.scrollback());
+}
+
+#[test]
+fn rates() {
+    let mut progress = progress(60, 5).with_rates(true);
+    let a = progress.bar(100, "A");
+    let b = progress.bar(100, "B");
+    assert_eq!(
+        "A                  [-----------]   0% 00:00   0 /s ETA --:--",
+        progress.writer().lines()[0]
+    );
+
+    progress.set_and_draw(&a, 100);
+    progress.cancel(b);
+
+    let lines = progress.writer().lines();
+    assert!(lines[0].starts_with("A             100  [###########] 100% 00:00 "));
+    assert!(lines[0].ends_with("/s ETA 00:00"));
+    assert!(lines[1].starts_with("B             100  [___________] ???  00:00 "));
+    assert!(lines[1].ends_with("/s ETA --:--"));
+    assert!(lines.iter().all(|line| line.chars().count() <= 60));
+}
+
+#[test]
+fn indeterminate() {
+    let mut progress = progress(40, 5);
+    let a = progress.indeterminate("A");
+    let b = progress.indeterminate("B");
+    assert_eq!(
+        "A                   [-------------]",
+        progress.writer().lines()[0]
+    );
+
+    // Animation frames only advance so often.
+    std::thread::sleep(std::time::Duration::from_millis(150));
+    progress.inc_and_draw(&a, 1500);
+    progress.inc_and_draw(&b, 10);
+
+    let lines = progress.writer().lines();
+    assert!(lines[0].starts_with("A                1K ["), "{:?}", lines);
+    assert!(lines[0].contains("<=>"));
+    assert!(!progress.is_done(&a));
+
+    // Upgrade to a regular bar once the total is known.
+    progress.set_total(&a, 3000);
+    progress.draw(&a);
+    assert_eq!(
+        "A                1K [######>------]  50%",
+        progress.writer().lines()[0]
+    );
+
+    progress.cancel(b);
+    assert_eq!(
+        "B               10  [_____________] ???",
+        progress.writer().lines()[1]
+    );
+}
+
+#[test]
+fn ticker() {
+    let progress = Arc::new(Mutex::new(progress(40, 5)));
+    let ticker = Ticker::spawn(progress.clone(), 60);
+    let bar = progress.lock().unwrap().bar(10, "A");
+
+    for _ in 0..5 {
+        progress.lock().unwrap().inc(&bar, 1);
+    }
+
+    // Stopping the ticker always draws one last time.
+    drop(ticker);
+    assert_eq!(
+        "A                5  [######>------]  50%",
+        progress.lock().unwrap().writer().contents()
+    );
+}
+
+#[test]
+fn shared_bars() {
+    let progress = Arc::new(Mutex::new(progress(40, 5)));
+    let ticker = Ticker::spawn(progress.clone(), 60);
+    let (a, shared) = {
+        let mut p = progress.lock().unwrap();
+        let a = p.bar(1000, "A");
+        let shared = p.share(&a);
+        (a, shared)
+    };
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            std::thread::spawn(move || (0..250).for_each(|_| shared.inc(1)))
+        })
+        .collect();
+    threads.into_iter().for_each(|t| t.join().unwrap());
+
+    drop(ticker);
+    let p = progress.lock().unwrap();
+    assert_eq!(1000, shared.get());
+    assert!(p.is_done(&a));
+    assert_eq!(
+        "A                1K [#############] 100%",
+        p.writer().contents()
+    );
+}
+
+#[test]
+fn styles() {
+    let mut progress = progress(40, 5).with_style(BarStyle::blocks());
+    let a = progress.bar(100, "A");
+    let b = progress.bar(100, "B");
+    let c = progress.bar(100, "C");
+    progress.set_style(&c, BarStyle::new('=', '>', ' ').with_cancelled('x'));
+
+    progress.set_and_draw(&a, 50);
+    progress.set_and_draw(&b, 100);
+    progress.set_and_draw(&c, 50);
+
+    let expected = "\
+A               50  [██████▌      ]  50%
+B              100  [█████████████] 100%
+C               50  [======>      ]  50%";
+    assert_eq!(expected, progress.writer().contents());
+
+    progress.cancel(a);
+    progress.cancel(c);
+    assert_eq!(
+        "A              100  [░░░░░░░░░░░░░] ???",
+        progress.writer().lines()[0]
+    );
+    assert_eq!(
+        "C              100  [xxxxxxxxxxxxx] ???",
+        progress.writer().lines()[2]
+    );
+}
+
+#[test]
+fn partials() {
+    let style = BarStyle::new('#', '>', '.').with_partials(vec!['.', ':']);
+    let mut progress = progress(40, 5).with_style(style);
+    let bar = progress.bar(26, "A");
+
+    // Half a character's worth of progress.
+    progress.set_and_draw(&bar, 1);
+    assert!(progress.writer().contents().contains("[:............]"));
+
+    progress.set_and_draw(&bar, 3);
+    assert!(progress.writer().contents().contains("[#:...........]"));
+}
+
+#[test]
+fn colors() {
+    let colors = Colors::new()
+        .with_progress(Color::Cyan)
+        .with_done(Color::Green)
+        .with_cancelled(Color::White.on(Color::Red));
+
+    let mut raw = Progress::with_writer(Vec::new())
+        .with_size(40, 5)
+        .with_colors(colors)
+        .with_color(ColorChoice::Always);
+    let a = raw.bar(100, "A");
+    let b = raw.bar(100, "B");
+    raw.set_colors(&b, Colors::new().with_progress(Color::Rgb(255, 128, 0)));
+    raw.set_and_draw(&a, 50);
+    raw.set_and_draw(&b, 50);
+    raw.cancel(a);
+
+    let out = String::from_utf8_lossy(raw.writer());
+    assert!(out.contains("[\x1B[36m######>------\x1B[0m]"));
+    assert!(out.contains("[\x1B[38;2;255;128;0m######>------\x1B[0m]"));
+    assert!(out.contains("[\x1B[37;41m_____________\x1B[0m]"));
+
+    // Colours take up no space in the layout.
+    let mut vt = progress(40, 5)
+        .with_colors(colors)
+        .with_color(ColorChoice::Always);
+    let a = vt.bar(100, "A");
+    vt.set_and_draw(&a, 100);
+    assert_eq!(
+        "A              100  [#############] 100%",
+        vt.writer().contents()
+    );
+
+    // Nor are they shown when turned off.
+    let mut plain = Progress::with_writer(Vec::new())
+        .with_size(40, 5)
+        .with_colors(colors)
+        .with_color(ColorChoice::Never);
+    let a = plain.bar(100, "A");
+    plain.set_and_draw(&a, 100);
+    assert!(!String::from_utf8_lossy(plain.writer()).contains("\x1B[3"));
+}
+
+#[test]
+fn resize() {
+    let mut progress = progress(40, 5);
+    let a = progress.bar(100, "A");
+    let b = progress.bar(100, "B");
+    progress.set_and_draw(&a, 50);
+    progress.set_and_draw(&b, 100);
+
+    progress.resize(30, 5);
+    let expected = "\
+A          50  [####>---]  50%
+B         100  [########] 100%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // Growing again lays the bars back out at full width.
+    progress.resize(40, 5);
+    let expected = "\
+A               50  [######>------]  50%
+B              100  [#############] 100%";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn resize_shorter() {
+    let mut progress = progress(40, 5);
+    let bars: Vec<_> = (0..4)
+        .map(|n| progress.bar(100, format!("{}", n)))
+        .collect();
+    bars.iter().for_each(|bar| progress.set_and_draw(bar, 100));
+
+    // Only the lines still on screen are redrawn, without touching the others.
+    progress.resize(20, 3);
+    let lines = progress.writer().lines();
+    assert_eq!("0              100  [#############] 100%", lines[0]);
+    assert_eq!("1              100  [#############] 100%", lines[1]);
+    assert_eq!("+3 more (3 done)", lines[2]);
+    assert!(lines[3].starts_with("3"));
+    assert!(lines[3].chars().count() <= 20);
+}
+
+#[test]
+fn on_finish_clear() {
+    let mut progress = progress(40, 5).with_on_finish(OnFinish::Clear);
+    let a = progress.bar(100, "A");
+    let b = progress.bar(100, "B");
+    let c = progress.bar(100, "C");
+    progress.set_on_finish(&c, OnFinish::Leave);
+
+    progress.set_and_draw(&b, 50);
+    progress.set_and_draw(&a, 100);
+    let expected = "\
+B               50  [######>------]  50%
+C                0  [>------------]   0%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // Bars below a cleared one are still drawn in the right place.
+    progress.set_and_draw(&c, 100);
+    progress.set_and_draw(&b, 75);
+    let expected = "\
+B               75  [#########>---]  75%
+C              100  [#############] 100%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // Messages are written above the remaining bars.
+    writeln!(progress.stderr(), "Hello").unwrap();
+    let expected = "\
+Hello
+B               75  [#########>---]  75%
+C              100  [#############] 100%";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn viewport() {
+    // Room for only two lines of bars.
+    let mut progress = progress(40, 3);
+    let bars: Vec<_> = (0..4)
+        .map(|n| progress.bar(100, format!("{}", n)))
+        .collect();
+    let expected = "\
++3 more (0 done)
+0                0  [>------------]   0%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // Folded bars aren't drawn.
+    progress.set_and_draw(&bars[2], 50);
+    assert_eq!(expected, progress.writer().contents());
+
+    // Finished bars make way for active ones.
+    progress.set_and_draw(&bars[0], 100);
+    let expected = "\
++3 more (1 done)
+1                0  [>------------]   0%";
+    assert_eq!(expected, progress.writer().contents());
+
+    progress.set_and_draw(&bars[1], 100);
+    let expected = "\
++3 more (2 done)
+2               50  [######>------]  50%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // Once all are done, the latest remains.
+    progress.set_and_draw(&bars[3], 100);
+    progress.set_and_draw(&bars[2], 100);
+    let expected = "\
++3 more (3 done)
+3              100  [#############] 100%";
+    assert_eq!(expected, progress.writer().contents());
+    assert!(progress.writer().scrollback().is_empty());
+}
+
+#[test]
+fn viewport_cleared() {
+    let mut progress = progress(40, 3).with_on_finish(OnFinish::Clear);
+    let bars: Vec<_> = (0..4)
+        .map(|n| progress.bar(100, format!("{}", n)))
+        .collect();
+    progress.set_and_draw(&bars[3], 50);
+    progress.set_and_draw(&bars[0], 100);
+    let expected = "\
++2 more (0 done)
+1                0  [>------------]   0%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // Cleared bars leave room for all the others.
+    progress.set_and_draw(&bars[1], 100);
+    let expected = "\
+2                0  [>------------]   0%
+3               50  [######>------]  50%";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn on_finish_summarize() {
+    let mut progress = progress(40, 5).with_on_finish(OnFinish::Summarize);
+    let a = progress.bar(2_000_000, "A");
+    let b = progress.bar(100, "B");
+    let c = progress.bar(100, "C");
+
+    progress.set_and_draw(&a, 2_000_000);
+    progress.set_and_draw(&b, 50);
+    progress.cancel(c);
+
+    let lines = progress.writer().lines();
+    assert!(lines[0].starts_with("✓ A — 2M in "), "{}", lines[0]);
+    assert!(lines[0].ends_with('s'));
+    assert_eq!("B               50  [######>------]  50%", lines[1]);
+    assert!(lines[2].starts_with("✗ C — 100, cancelled after "));
+}
+
+#[test]
+fn aggregate() {
+    let mut progress = progress(40, 6);
+    let all = progress.aggregate("All", Pin::Top);
+    let a = progress.bar(100, "A");
+    let b = progress.bar(100, "B");
+    let c = progress.bar(200, "C");
+
+    progress.inc_and_draw(&a, 100);
+    progress.inc_and_draw(&b, 50);
+    let expected = "\
+All            150  [####>--------]  37%
+A              100  [#############] 100%
+B               50  [######>------]  50%
+C                   [-------------]   0%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // Cancelled bars count as complete.
+    progress.cancel(c);
+    assert_eq!(
+        "All            350  [###########>-]  87%",
+        progress.writer().lines()[0]
+    );
+
+    progress.inc_and_draw(&b, 50);
+    assert!(progress.is_done(&all));
+    assert_eq!(
+        "All            400  [#############] 100%",
+        progress.writer().lines()[0]
+    );
+}
+
+#[test]
+fn aggregate_pinned() {
+    // Room for only three lines of bars.
+    let mut progress = progress(40, 4);
+    let a = progress.bar(100, "A");
+    let b = progress.indeterminate("B");
+    let group = progress.aggregate_of("A+B", Pin::Bottom, &[&a, &b]);
+    let _ = progress.bar(100, "C");
+
+    // Pinned bars are never folded away.
+    progress.inc_and_draw(&a, 50);
+    progress.set_total(&b, 100);
+    progress.inc_and_draw(&b, 100);
+    let expected = "\
++2 more (1 done)
+A               50  [######>------]  50%
+A+B            150  [#########>---]  75%";
+    assert_eq!(expected, progress.writer().contents());
+    assert!(!progress.is_done(&group));
+}
+
+#[test]
+fn nested() {
+    let mut progress = progress(40, 8);
+    let build = progress.bar(2, "Build");
+    let download = progress.indeterminate("Download");
+    progress.sum_children(&download);
+    let core = progress.child_bar(&build, 100, "core");
+    let a = progress.child_bar(&download, 100, "a");
+    let cli = progress.child_bar(&build, 100, "cli");
+    let b = progress.child_bar(&download, 300, "b");
+
+    progress.inc_and_draw(&core, 100);
+    progress.inc_and_draw(&build, 1);
+    progress.inc_and_draw(&a, 50);
+    progress.inc_and_draw(&b, 150);
+    let expected = "\
+Build            1  [######>------]  50%
+  core          100  [############] 100%
+  cli             0  [>-----------]   0%
+Download       200  [######>------]  50%
+  a              50  [######>-----]  50%
+  b             150  [######>-----]  50%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // A finished parent collapses its children.
+    progress.inc_and_draw(&cli, 100);
+    progress.inc_and_draw(&build, 1);
+    let expected = "\
+Build            2  [#############] 100%
+Download       200  [######>------]  50%
+  a              50  [######>-----]  50%
+  b             150  [######>-----]  50%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // Including those whose progress they're derived from.
+    progress.inc_and_draw(&a, 50);
+    progress.inc_and_draw(&b, 150);
+    assert!(progress.is_done(&download));
+    let expected = "\
+Build            2  [#############] 100%
+Download       400  [#############] 100%";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn iterators() {
+    let mut progress = progress(40, 5);
+    let sum: usize = (0..10).progress_with(&mut progress, "A").sum();
+    assert_eq!(45, sum);
+
+    // Without an exact size, the total is only known at the end.
+    let evens = (0..10)
+        .filter(|n| n % 2 == 0)
+        .progress_with(&mut progress, "B")
+        .count();
+    assert_eq!(5, evens);
+
+    // Stopping early cancels the bar.
+    for n in (0..10).progress_with(&mut progress, "C") {
+        if n == 5 {
+            break;
+        }
+    }
+
+    let _ = (0..0).progress_with(&mut progress, "D").count();
+
+    let expected = "\
+A               10  [#############] 100%
+B                5  [#############] 100%
+C               10  [_____________] ???
+D                0  [#############] 100%";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn iterators_shared() {
+    let progress = Mutex::new(progress(40, 5));
+
+    std::thread::scope(|s| {
+        for t in 0..2 {
+            let progress = &progress;
+            s.spawn(move || (0..50).progress_with(progress, format!("{}", t)).count());
+        }
+    });
+
+    let p = progress.lock().unwrap();
+    assert!(p
+        .writer()
+        .lines()
+        .iter()
+        .take(2)
+        .all(|l| l.ends_with("50  [#############] 100%")));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_iterators() {
+    use linya::ParallelProgressIterator;
+    use rayon::prelude::*;
+
+    let mut progress = progress(40, 5);
+    let sum: usize = (0..1000)
+        .into_par_iter()
+        .progress_with(&mut progress, "A")
+        .sum();
+    assert_eq!(499500, sum);
+
+    let shared = Mutex::new(progress);
+    let evens: Vec<_> = (0..100)
+        .into_par_iter()
+        .progress_with(&shared, "B")
+        .filter(|n| n % 2 == 0)
+        .collect();
+    assert_eq!(50, evens.len());
+
+    let expected = "\
+A                1K [#############] 100%
+B              100  [#############] 100%";
+    assert_eq!(expected, shared.lock().unwrap().writer().contents());
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn streams() {
+    use futures::{executor, stream, StreamExt};
+    use linya::ProgressStream;
+
+    let progress = Arc::new(Mutex::new(progress(40, 5)));
+
+    executor::block_on(async {
+        let sum: u32 = stream::iter(0..100)
+            .progress_with(progress.clone(), "A")
+            .fold(0, |acc, n| async move { acc + n })
+            .await;
+        assert_eq!(4950, sum);
+
+        // Without an exact size hint, the total is only known at the end.
+        let evens = stream::iter(0..100).filter(|n| futures::future::ready(n % 2 == 0));
+        let count = evens.progress_with(progress.clone(), "B").count().await;
+        assert_eq!(50, count);
+
+        // Stopping early cancels the bar.
+        let firsts: Vec<_> = stream::iter(0..100)
+            .progress_with(progress.clone(), "C")
+            .take(25)
+            .collect()
+            .await;
+        assert_eq!(25, firsts.len());
+
+        // Streams of `async` blocks can't be moved once polled.
+        let doubled: Vec<_> = stream::iter(0..10)
+            .then(|n| async move { n * 2 })
+            .progress_with(progress.clone(), "D")
+            .collect()
+            .await;
+        assert_eq!(10, doubled.len());
+    });
+
+    let expected = "\
+A              100  [#############] 100%
+B               50  [#############] 100%
+C              100  [_____________] ???
+D               10  [#############] 100%";
+    assert_eq!(expected, progress.lock().unwrap().writer().contents());
+}
+
+#[cfg(all(feature = "tokio", feature = "futures"))]
+#[test]
+fn async_readers_and_writers() {
+    use futures::executor;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let data = vec![b'x'; 3000];
+    let progress = Arc::new(Mutex::new(progress(40, 5)));
+
+    executor::block_on(async {
+        let bar = progress.lock().unwrap().bar(data.len(), "A");
+        let mut reader = ProgressReader::new(&data[..], progress.clone(), bar);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(3000, buf.len());
+
+        let bar = progress.lock().unwrap().bar(data.len(), "B");
+        let mut writer = ProgressWriter::new(Vec::new(), progress.clone(), bar);
+        AsyncWriteExt::write_all(&mut writer, &data[..1500])
+            .await
+            .unwrap();
+        assert_eq!(1500, writer.get_ref().len());
+
+        let bar = progress.lock().unwrap().bar(data.len(), "C");
+        let mut reader = ProgressReader::new(&data[..], progress.clone(), bar);
+        let mut buf = vec![0; 750];
+        futures::io::AsyncReadExt::read_exact(&mut reader, &mut buf)
+            .await
+            .unwrap();
+    });
+
+    let expected = "\
+A                3K [#############] 100%
+B                1K [######>------]  50%
+C              750  [###>---------]  25%";
+    assert_eq!(expected, progress.lock().unwrap().writer().contents());
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn shared_async_adapters() {
+    use futures::io::{AsyncReadExt, AsyncWriteExt};
+    use futures::{executor, stream, StreamExt};
+    use linya::ProgressStream;
+
+    let data = vec![b'x'; 3000];
+    let mut progress = progress(40, 5);
+    let a = progress.bar(100, "A");
+    let b = progress.bar(data.len(), "B");
+    let c = progress.bar(data.len(), "C");
+    let (sa, sb, sc) = (progress.share(&a), progress.share(&b), progress.share(&c));
+
+    // Nothing is locked or drawn from within the tasks.
+    executor::block_on(async {
+        let count = stream::iter(0..100).progress_shared(sa).count().await;
+        assert_eq!(100, count);
+
+        let mut reader = ProgressReader::shared(&data[..], sb);
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await.unwrap();
+
+        let mut writer = ProgressWriter::shared(Vec::new(), sc);
+        AsyncWriteExt::write_all(&mut writer, &data[..1500])
+            .await
+            .unwrap();
+    });
+    assert_eq!(
+        "A                   [-------------]   0%",
+        progress.writer().lines()[0]
+    );
+
+    progress.draw_all();
+    let expected = "\
+A              100  [#############] 100%
+B                3K [#############] 100%
+C                1K [######>------]  50%";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn checked_operations() {
+    let mut progress = progress(40, 5);
+    assert_eq!(Some(Error::ZeroTotal), progress.try_bar(0, "A").err());
+
+    let a = progress.try_bar(10, "A").unwrap();
+    assert_eq!(Ok(()), progress.try_inc_and_draw(&a, 4));
+    assert_eq!(Ok(false), progress.try_is_done(&a));
+    assert_eq!(Ok(()), progress.try_set_and_draw(&a, 10));
+    assert_eq!(Ok(true), progress.try_is_done(&a));
+    assert_eq!(Err(Error::Finished), progress.try_inc(&a, 1));
+    assert_eq!(Err(Error::Finished), progress.try_set_total(&a, 20));
+    assert_eq!(Err(Error::Finished), progress.try_cancel(a));
+
+    let b = progress.bar(10, "B");
+    assert_eq!(Ok(()), progress.try_cancel(b));
+    let c = progress.indeterminate("C");
+    assert_eq!(Ok(()), progress.try_set_total(&c, 10));
+
+    // A bar of another `Progress`, though one with the same index exists here.
+    let mut other = Progress::with_writer(VirtualTerminal::new(40, 5)).with_size(40, 5);
+    let d = other.bar(10, "D");
+    assert_eq!(Err(Error::ForeignBar), progress.try_set(&d, 1));
+    assert_eq!(Err(Error::ForeignBar), progress.try_is_done(&d));
+    assert_eq!(Err(Error::ForeignBar), progress.try_set_total(&d, 20));
+    assert_eq!(Err(Error::ForeignBar), progress.try_cancel(d));
+
+    assert_eq!(Ok(()), progress.try_inc_and_draw(&c, 1));
+
+    let expected = "\
+A               10  [#############] 100%
+B               10  [_____________] ???
+C                1  [#>-----------]  10%";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "didn't make it")]
+fn foreign_bars() {
+    let mut a = progress(40, 5);
+    let mut b = progress(40, 5);
+    let bar = a.bar(10, "A");
+    b.inc_and_draw(&bar, 1);
+}
+
+#[test]
+fn plain_lines() {
+    let plain = Plain::new().with_step(25);
+    let mut progress = Progress::with_writer(Vec::new()).with_plain(plain);
+
+    let a = progress.bar(1000, "Downloading #3");
+    for _ in 0..17 {
+        progress.inc_and_draw(&a, 45);
+    }
+    progress.inc_and_draw(&a, 235);
+
+    let b = progress.indeterminate("Counting");
+    progress.inc_and_draw(&b, 10);
+    progress.cancel(b);
+
+    let expected = "\
+Downloading #3: 0% (0/1000)
+Downloading #3: 27% (270/1000)
+Downloading #3: 54% (540/1000)
+Downloading #3: 76% (765/1000)
+Downloading #3: 100% (1000/1000)
+Counting: 0
+Counting: cancelled
+";
+    assert_eq!(expected, String::from_utf8_lossy(progress.writer()));
+}
+
+#[test]
+fn events() {
+    /// An event target that can still be read once given away.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let events = Shared::default();
+    let mut progress = progress(40, 5).with_events(events.clone());
+
+    let a = progress.bar(1000, "Downloading \"a\"");
+    progress.inc_and_draw(&a, 450);
+    writeln!(progress.stderr(), "Mirror is slow").unwrap();
+    progress.inc_and_draw(&a, 550);
+
+    let b = progress.child_bar(&a, 10, "B");
+    progress.inc(&b, 5); // Never drawn.
+    progress.cancel(b);
+
+    let expected = r#"{"event":"bar_created","bar":0,"label":"Downloading \"a\"","total":1000,"parent":null}
+{"event":"progress","bar":0,"value":450,"total":1000}
+{"event":"message","text":"Mirror is slow"}
+{"event":"progress","bar":0,"value":1000,"total":1000}
+{"event":"finished","bar":0,"value":1000}
+{"event":"bar_created","bar":1,"label":"B","total":10,"parent":0}
+{"event":"cancelled","bar":1,"value":5}
+"#;
+    assert_eq!(expected, String::from_utf8_lossy(&events.0.lock().unwrap()));
+}
+
+#[test]
+fn progress_is_sync() {
+    fn assert_sync<T: Sync>() {}
+
+    // Whatever the events are written to, a `Progress` can still be shared by
+    // reference between threads.
+    assert_sync::<Progress>();
+    assert_sync::<Progress<VirtualTerminal>>();
+}
+
+#[test]
+fn mirrors() {
+    let mut progress = progress(40, 5).with_mirror(Mirror::Taskbar);
+    let a = progress.bar(100, "A");
+    let b = progress.bar(300, "B");
+    progress.inc_and_draw(&a, 100);
+    assert_eq!(Some((1, 25)), progress.writer().taskbar());
+    progress.inc_and_draw(&b, 150);
+    assert_eq!(Some((1, 62)), progress.writer().taskbar());
+
+    // Cancelled bars count as complete, so all is done.
+    progress.cancel(b);
+    assert_eq!(None, progress.writer().taskbar());
+
+    let c = progress.indeterminate("C");
+    progress.inc_and_draw(&c, 1);
+    assert_eq!(Some((3, 0)), progress.writer().taskbar());
+
+    // New bars count towards the overall progress as soon as they're made.
+    let mut progress = Progress::with_writer(VirtualTerminal::new(40, 5))
+        .with_size(40, 5)
+        .with_mirror(Mirror::Taskbar);
+    let a = progress.bar(10, "A");
+    progress.inc_and_draw(&a, 3);
+    assert_eq!(Some((1, 30)), progress.writer().taskbar());
+    progress.bar(10, "B");
+    assert_eq!(Some((1, 15)), progress.writer().taskbar());
+
+    // A single bar can be shown instead, here in the title.
+    let mut vt = VirtualTerminal::new(40, 5);
+    write!(vt, "\x1B]2;Shell\x07").unwrap();
+    let mut progress = Progress::with_writer(vt)
+        .with_size(40, 5)
+        .with_mirror(Mirror::Title);
+    let a = progress.bar(100, "A");
+    let b = progress.bar(10, "B");
+    progress.mirror_bar(&b);
+    progress.inc_and_draw(&a, 50);
+    assert_eq!(Some("0% B"), progress.writer().title());
+    progress.inc_and_draw(&b, 4);
+    assert_eq!(Some("40% B"), progress.writer().title());
+    progress.inc_and_draw(&b, 6);
+    assert_eq!(Some("Shell"), progress.writer().title());
+
+    // Terminals that can't restore the old title are left with an empty one.
+    let mut progress = Progress::with_writer(Vec::new())
+        .with_size(40, 5)
+        .with_mirror(Mirror::Title);
+    let a = progress.bar(10, "A");
+    progress.inc_and_draw(&a, 5);
+    progress.inc_and_draw(&a, 5);
+    let out = String::from_utf8_lossy(progress.writer());
+    assert!(out.ends_with("\x1B]2;\x1B\\\x1B[23;2t"), "{:?}", out);
+}
+
+#[test]
+fn mirrors_are_cleared_on_drop() {
+    let vt = Arc::new(Mutex::new(VirtualTerminal::new(40, 5)));
+
+    /// A terminal that can still be read once the `Progress` is gone.
+    struct Shared(Arc<Mutex<VirtualTerminal>>);
+
+    impl std::io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut progress = Progress::with_writer(Shared(vt.clone()))
+        .with_size(40, 5)
+        .with_mirror(Mirror::Taskbar);
+    let a = progress.bar(100, "A");
+    progress.inc_and_draw(&a, 50);
+    assert_eq!(Some((1, 50)), vt.lock().unwrap().taskbar());
+
+    drop(progress);
+    assert_eq!(None, vt.lock().unwrap().taskbar());
+}
+
+#[test]
+fn messages() {
+    let mut progress = progress(40, 5);
+    let a = progress.bar(10, "A");
+    let b = progress.bar(10, "Longer");
+    progress.set_message(&a, "a.txt");
+    progress.set_message(&b, "retrying\n2/5");
+    progress.draw(&a);
+    progress.draw(&b);
+
+    // Messages are cut short, so that the bars stay in line.
+    let expected = "\
+A a.txt          0  [>------------]   0%
+Longer retryi…   0  [>------------]   0%";
+    assert_eq!(expected, progress.writer().contents());
+
+    // As are tabs and other control characters, which have no fixed width.
+    progress.set_message(&a, "x\ty\x1Bz");
+    progress.draw(&a);
+    assert_eq!(
+        "A x y z          0  [>------------]   0%",
+        progress.writer().lines()[0]
+    );
+
+    progress.set_message(&b, "");
+    progress.inc_and_draw(&b, 5);
+    assert_eq!(
+        "Longer           5  [######>------]  50%",
+        progress.writer().lines()[1]
+    );
+}
+
+#[test]
+fn readers_and_writers() {
+    use std::io::{BufRead, Cursor, Seek, SeekFrom};
+
+    let data = vec![b'x'; 3000];
+    let mut progress = progress(40, 5);
+
+    let bar = progress.bar(data.len(), "A");
+    let mut reader = ProgressReader::new(&data[..], &mut progress, bar);
+    std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+
+    let bar = progress.bar(data.len(), "B");
+    let mut writer = ProgressWriter::new(Vec::new(), &mut progress, bar);
+    writer.write_all(&data[..1500]).unwrap();
+    assert_eq!(1500, writer.get_ref().len());
+
+    // Lines consumed from a buffered reader count as read.
+    let text = "one\ntwo\nthree\n";
+    let bar = progress.bar(text.len(), "C");
+    let mut reader = ProgressReader::new(text.as_bytes(), &mut progress, bar);
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!("one\n", line);
+
+    // Seeking moves the bar along.
+    let bar = progress.bar(data.len(), "D");
+    let mut reader = ProgressReader::new(Cursor::new(&data), &mut progress, bar);
+    reader.seek(SeekFrom::End(-750)).unwrap();
+
+    let expected = "\
+A                3K [#############] 100%
+B                1K [######>------]  50%
+C                4  [###>---------]  28%
+D                2K [#########>---]  75%";
+    assert_eq!(expected, progress.writer().contents());
+}
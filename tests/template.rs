@@ -0,0 +1,81 @@
+use linya::{Progress, Template, TemplateError, VirtualTerminal};
+
+fn progress(template: &str) -> Progress<VirtualTerminal> {
+    Progress::with_writer(VirtualTerminal::new(40, 5))
+        .with_size(40, 5)
+        .with_template(template.parse().unwrap())
+}
+
+#[test]
+fn flexible_bar() {
+    let mut progress = progress("{label} [{bar}] {pos}/{len} {percent}");
+    let bar = progress.bar(1000, "Files");
+    progress.set_and_draw(&bar, 450);
+
+    assert_eq!(
+        "Files [########>---------] 450/1000  45%",
+        progress.writer().contents()
+    );
+}
+
+#[test]
+fn fixed_widths() {
+    let mut progress = progress("{label:8}|{count:>5}|{bar:10}|{{{len}}}");
+    let a = progress.bar(2000, "Downloading");
+    let b = progress.bar(2000, "A");
+    progress.set_and_draw(&a, 1500);
+    progress.set_and_draw(&b, 2000);
+
+    let expected = "\
+Download|   1K|#######>--|{2000}
+A       |   2K|##########|{2000}";
+    assert_eq!(expected, progress.writer().contents());
+}
+
+#[test]
+fn messages() {
+    let mut progress = progress("{label}: {msg:10}|{pos}");
+    let bar = progress.bar(10, "Files");
+    progress.set_message(&bar, "photos/2021/beach.jpg");
+    progress.set_and_draw(&bar, 3);
+
+    assert_eq!("Files: photos/202|3", progress.writer().contents());
+}
+
+#[test]
+fn per_bar_template() {
+    let mut progress = progress("{label}: {percent}");
+    let a = progress.bar(10, "A");
+    let b = progress.bar(10, "B");
+    progress.set_template(&b, Template::new("{label} is at {pos}").unwrap());
+    progress.set_and_draw(&a, 5);
+    progress.set_and_draw(&b, 5);
+
+    assert_eq!("A:  50%\nB is at 5", progress.writer().contents());
+}
+
+#[test]
+fn too_long() {
+    let mut progress = progress("{label} {bar:50}");
+    let bar = progress.bar(10, "A");
+    progress.set_and_draw(&bar, 10);
+
+    let lines = progress.writer().lines();
+    assert_eq!(format!("A {}", "#".repeat(38)), lines[0]);
+    assert_eq!("", lines[1]);
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        Err(TemplateError::UnknownKey("foo".to_string())),
+        Template::new("{label} {foo}")
+    );
+    assert_eq!(
+        Err(TemplateError::BadWidth("x".to_string())),
+        Template::new("{label:x}")
+    );
+    assert_eq!(Err(TemplateError::Unclosed), Template::new("{label"));
+    assert_eq!(Err(TemplateError::Unmatched), Template::new("label}"));
+    assert!(Template::new("{{label}} {eta} {spinner:>3}").is_ok());
+}
@@ -0,0 +1,44 @@
+//! Bars drawn to a real terminal, by way of `script` and a pseudo-terminal.
+
+#![cfg(target_os = "linux")]
+
+use linya::Progress;
+use std::process::Command;
+
+/// Set for the copy of this test that plays the child.
+const CHILD: &str = "LINYA_TEST_TTY_CHILD";
+
+/// Bars are drawn to `stderr`, so it's that terminal that counts, even when
+/// `stdout` has been redirected elsewhere.
+#[test]
+fn stdout_redirected() {
+    if std::env::var_os(CHILD).is_some() {
+        let mut progress = Progress::new();
+        let bar = progress.bar(10, "Hacking");
+        progress.set_and_draw(&bar, 10);
+        return;
+    }
+
+    // Run just this test again as the child, on a terminal of a known size
+    // but with its `stdout` thrown away.
+    let exe = std::env::current_exe().unwrap();
+    let child = format!(
+        "stty cols 40 rows 10; {} stdout_redirected --exact --nocapture --test-threads=1 > /dev/null",
+        exe.display()
+    );
+    let output = match Command::new("script")
+        .args(["-qec", &child, "/dev/null"])
+        .env(CHILD, "1")
+        .output()
+    {
+        Ok(output) => output,
+        // There's no terminal to be had without `script`.
+        Err(_) => return,
+    };
+    assert!(output.status.success());
+
+    // A full bar, and not the plain line of a `Progress` without a terminal.
+    let drawn = String::from_utf8_lossy(&output.stdout);
+    assert!(drawn.contains("[#############] 100%"), "{:?}", drawn);
+    assert!(!drawn.contains("Hacking: 100%"), "{:?}", drawn);
+}